@@ -1,336 +1,3373 @@
 #![allow(clippy::result_large_err)]
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 use anchor_lang::system_program;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token_interface::{
+    self, Burn, CloseAccount, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
 
 declare_id!("69UzMhGARnmJuWtjbSwEe2t2co2LNc2YGEX1Jun8K9RK");
 
 // Constants
-const MAKER_FEE_RATE: u64 = 200; // 2% (in basis points)
 const MAX_FEE_RATE: u64 = 1000; // 10% max fee rate
                                 // const MIN_BET_DURATION: i64 = 3600; // 1 hour minimum
 const MIN_BET_DURATION: i64 = 60; // 1 minutes minimum
 const MAX_STRING_LENGTH: usize = 200;
+const MAX_FEE_TIERS: usize = 5;
+const MAX_CATEGORY_NAME_LENGTH: usize = 50;
+const RANDOM_CLOSE_SLICES: usize = 8;
+const RANDOM_CLOSE_SEED_DELAY: u64 = 32; // slots between committing to a seed slot and reading its hash
+const MAX_BUCKETS: usize = 8;
+const MAX_PARLAY_LEGS: usize = 5;
+const DRAW_OUTCOME: u8 = u8::MAX; // winning_option of a drawn or pushed market
+const LP_VIRTUAL_SHARES: u64 = 1_000_000; // offsets share pricing so donations can't inflate the first deposit away
+const LP_VIRTUAL_ASSETS: u64 = 1_000_000;
 
 #[program]
 pub mod betting_dapp {
     use super::*;
 
-    /// Create a new betting market
-    pub fn create_bet(
-        ctx: Context<CreateBet>,
-        bet_id: String,
-        description: String,
-        option_a: String,
-        option_b: String,
-        end_time: i64,
-        min_bet_amount: u64,
-        max_bet_amount: u64,
-        category: String,
+    /// Initialize platform configuration
+    pub fn initialize_platform(
+        ctx: Context<InitializePlatform>,
+        max_end_time_extension: i64, // seconds a market may be pushed past its original end time
+        referral_fee_bps: u16,       // share of the maker fee paid to referrers
+        max_maker_fee_bps: u16,      // cap on the maker fee creators may set
     ) -> Result<()> {
-        let bet = &mut ctx.accounts.bet;
-        let clock = Clock::get()?;
+        let platform_config = &mut ctx.accounts.platform_config;
 
-        // Validations
         require!(
-            end_time > clock.unix_timestamp + MIN_BET_DURATION,
-            BettingError::InvalidEndTime
+            max_end_time_extension >= 0,
+            BettingError::InvalidPlatformConfig
         );
-        require!(min_bet_amount > 0, BettingError::InvalidAmount);
         require!(
-            max_bet_amount >= min_bet_amount,
-            BettingError::InvalidAmount
+            referral_fee_bps <= 10000,
+            BettingError::InvalidPlatformConfig
+        );
+        require!(
+            max_maker_fee_bps as u64 <= MAX_FEE_RATE,
+            BettingError::FeeTooHigh
         );
+
+        platform_config.owner = *ctx.accounts.owner.key;
+        platform_config.max_end_time_extension = max_end_time_extension;
+        platform_config.referral_fee_bps = referral_fee_bps;
+        platform_config.max_maker_fee_bps = max_maker_fee_bps;
+        platform_config.fee_tiers = Vec::new();
+        platform_config.arbiter = *ctx.accounts.owner.key;
+        platform_config.dispute_window = 0;
+        platform_config.resolution_grace_period = 0;
+        platform_config.resolution_deadline = 0;
+        platform_config.min_creator_bond = 0;
+        platform_config.permissioned_creation = false;
+        platform_config.max_open_exposure_per_user = 0;
+        platform_config.max_odds_bps = 100_000;
+        platform_config.max_parlay_payout_bps = 100;
+        platform_config.max_market_reserve_bps = 1000;
+        platform_config.bookmaker = Pubkey::default();
+        platform_config.bump = ctx.bumps.platform_config;
+
+        msg!("Platform initialized");
+        Ok(())
+    }
+
+    /// Update platform configuration (only platform owner can do this)
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        max_end_time_extension: i64,
+        referral_fee_bps: u16,
+        max_maker_fee_bps: u16,
+    ) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
         require!(
-            !category.is_empty() && category.len() <= 50,
-            BettingError::InvalidCategory
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
         );
         require!(
-            !description.is_empty() && description.len() <= MAX_STRING_LENGTH,
-            BettingError::InvalidDescription
+            max_end_time_extension >= 0,
+            BettingError::InvalidPlatformConfig
+        );
+        require!(
+            referral_fee_bps <= 10000,
+            BettingError::InvalidPlatformConfig
+        );
+        require!(
+            max_maker_fee_bps as u64 <= MAX_FEE_RATE,
+            BettingError::FeeTooHigh
         );
 
-        // Initialize bet state
-        bet.creator = *ctx.accounts.creator.key;
-        bet.bet_id = bet_id;
-        bet.description = description;
-        bet.option_a = option_a;
-        bet.option_b = option_b;
-        bet.end_time = end_time;
-        bet.total_amount_a = 0;
-        bet.total_amount_b = 0;
-        bet.is_resolved = false;
-        bet.winning_option = 0; // 0 = unresolved, 1 = option A, 2 = option B
-        bet.bump = ctx.bumps.bet;
-        bet.min_bet_amount = min_bet_amount;
-        bet.max_bet_amount = max_bet_amount;
-        bet.category = category;
-        bet.created_at = clock.unix_timestamp;
-        bet.resolved_at = 0;
-        bet.maker_fee_collected = 0;
-        bet.total_bettors = 0;
-        bet.result_details = String::new();
+        platform_config.max_end_time_extension = max_end_time_extension;
+        platform_config.referral_fee_bps = referral_fee_bps;
+        platform_config.max_maker_fee_bps = max_maker_fee_bps;
 
-        msg!("Bet created: {}", bet.bet_id);
+        msg!("Platform config updated");
         Ok(())
     }
 
-    /// Place a bet on an option
-    pub fn place_bet(
-        ctx: Context<PlaceBet>,
-        bet_id: String,
-        option: u8, // 1 for option A, 2 for option B
-        amount: u64,
+    /// Replace the volume-based fee schedule (only platform owner can do this)
+    pub fn set_fee_schedule(
+        ctx: Context<UpdatePlatformConfig>,
+        fee_tiers: Vec<FeeTier>,
     ) -> Result<()> {
-        let bet = &mut ctx.accounts.bet;
-        let user_bet = &mut ctx.accounts.user_bet;
-        let clock = Clock::get()?;
+        let platform_config = &mut ctx.accounts.platform_config;
 
-        // Validations
         require!(
-            clock.unix_timestamp < bet.end_time,
-            BettingError::BettingClosed
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+        require!(
+            fee_tiers.len() <= MAX_FEE_TIERS,
+            BettingError::InvalidFeeSchedule
+        );
+        require!(
+            fee_tiers
+                .windows(2)
+                .all(|pair| pair[0].min_volume < pair[1].min_volume),
+            BettingError::InvalidFeeSchedule
+        );
+        require!(
+            fee_tiers.iter().all(|tier| tier.discount_bps <= 10000),
+            BettingError::InvalidFeeSchedule
         );
-        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
-        require!(option == 1 || option == 2, BettingError::InvalidOption);
-        require!(amount > 0, BettingError::InvalidAmount);
-        require!(amount >= bet.min_bet_amount, BettingError::BetTooLow);
-        require!(amount <= bet.max_bet_amount, BettingError::BetTooHigh);
 
-        // Calculate maker fee
-        let maker_fee = calculate_maker_fee(amount);
-        let net_amount = amount - maker_fee;
+        platform_config.fee_tiers = fee_tiers;
 
-        // Update bet totals
-        if option == 1 {
-            bet.total_amount_a += net_amount;
-        } else {
-            bet.total_amount_b += net_amount;
-        }
+        msg!("Fee schedule updated");
+        Ok(())
+    }
 
-        bet.total_bettors += 1;
-        bet.maker_fee_collected += maker_fee;
+    /// Set who arbitrates disputes and the resolution timing rules (only platform owner can do this)
+    pub fn set_resolution_policy(
+        ctx: Context<UpdatePlatformConfig>,
+        arbiter: Pubkey,
+        dispute_window: i64, // seconds after resolution before winnings can be claimed
+        resolution_grace_period: i64, // seconds after end time a resolution still counts as on time
+        resolution_deadline: i64, // seconds after end time before anyone may void the market (0 = never)
+        min_creator_bond: u64,
+    ) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
 
-        // Transfer SOL from user to bet account
-        let transfer_instruction = system_program::Transfer {
-            from: ctx.accounts.user.to_account_info(),
-            to: ctx.accounts.bet.to_account_info(),
-        };
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                transfer_instruction,
-            ),
-            amount,
-        )?;
+        require!(
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+        require!(
+            dispute_window >= 0 && resolution_grace_period >= 0 && resolution_deadline >= 0,
+            BettingError::InvalidPlatformConfig
+        );
 
-        // Store user's bet
-        user_bet.user = *ctx.accounts.user.key;
-        user_bet.bet_id = bet_id;
-        user_bet.option = option;
-        user_bet.amount = net_amount;
-        user_bet.is_claimed = false;
-        user_bet.bump = ctx.bumps.user_bet;
-        user_bet.placed_at = clock.unix_timestamp;
-        user_bet.original_amount = amount;
-        user_bet.claimed_at = 0;
+        platform_config.arbiter = arbiter;
+        platform_config.dispute_window = dispute_window;
+        platform_config.resolution_grace_period = resolution_grace_period;
+        platform_config.resolution_deadline = resolution_deadline;
+        platform_config.min_creator_bond = min_creator_bond;
 
-        msg!("Bet placed: {} SOL on option {}", amount, option);
+        msg!("Resolution policy updated");
         Ok(())
     }
 
-    /// Resolve a bet (only creator can do this)
-    pub fn resolve_bet(
-        ctx: Context<ResolveBet>,
-        _bet_id: String,
-        winning_option: u8,
-        result_details: String,
+    /// Switch between open and allowlisted market creation (only platform owner can do this)
+    pub fn set_creation_mode(
+        ctx: Context<UpdatePlatformConfig>,
+        permissioned_creation: bool,
     ) -> Result<()> {
-        let bet = &mut ctx.accounts.bet;
-        let clock = Clock::get()?;
+        let platform_config = &mut ctx.accounts.platform_config;
 
-        // Validations
-        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
         require!(
-            winning_option == 1 || winning_option == 2,
-            BettingError::InvalidOption
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
         );
+
+        platform_config.permissioned_creation = permissioned_creation;
+
+        msg!("Permissioned creation: {}", permissioned_creation);
+        Ok(())
+    }
+
+    /// Cap how much a single wallet may have staked across unsettled markets (only platform owner can do this)
+    pub fn set_exposure_limit(
+        ctx: Context<UpdatePlatformConfig>,
+        max_open_exposure_per_user: u64, // 0 = unlimited
+    ) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
         require!(
-            bet.creator == *ctx.accounts.creator.key,
-            BettingError::UnauthorizedResolver
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
         );
+
+        platform_config.max_open_exposure_per_user = max_open_exposure_per_user;
+
+        msg!("Exposure limit updated");
+        Ok(())
+    }
+
+    /// Cap the odds and payouts the house vault will take on (only platform owner can do this)
+    pub fn set_house_limits(
+        ctx: Context<UpdatePlatformConfig>,
+        max_odds_bps: u64,           // highest price the house pays on a single leg
+        max_parlay_payout_bps: u16,  // largest parlay payout as a share of the vault
+        max_market_reserve_bps: u16, // largest reserve one fixed-odds market may draw from the vault
+    ) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
         require!(
-            clock.unix_timestamp >= bet.end_time,
-            BettingError::BettingStillOpen
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
         );
         require!(
-            result_details.len() <= 300,
-            BettingError::InvalidResultDetails
+            max_odds_bps > 10000
+                && max_parlay_payout_bps <= 10000
+                && max_market_reserve_bps <= 10000,
+            BettingError::InvalidPlatformConfig
         );
 
-        bet.is_resolved = true;
-        bet.winning_option = winning_option;
-        bet.resolved_at = clock.unix_timestamp;
-        bet.result_details = result_details;
+        platform_config.max_odds_bps = max_odds_bps;
+        platform_config.max_parlay_payout_bps = max_parlay_payout_bps;
+        platform_config.max_market_reserve_bps = max_market_reserve_bps;
 
-        msg!("Bet resolved: option {} won", winning_option);
+        msg!("House limits updated");
         Ok(())
     }
 
-    /// Claim maker fees (only bet creator can do this)
-    pub fn claim_maker_fees(ctx: Context<ClaimMakerFees>, _bet_id: String) -> Result<()> {
-        let bet = &mut ctx.accounts.bet;
+    /// Approve the bookmaker allowed to run house-backed fixed-odds markets, default = none
+    /// (only platform owner can do this)
+    pub fn set_bookmaker(ctx: Context<UpdatePlatformConfig>, bookmaker: Pubkey) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        require!(
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+
+        platform_config.bookmaker = bookmaker;
+
+        msg!("Bookmaker approved: {}", bookmaker);
+        Ok(())
+    }
 
+    /// Set up the LP-owned house vault and its share mint (only platform owner can do this)
+    pub fn configure_liquidity_vault(
+        ctx: Context<ConfigureLiquidityVault>,
+        withdrawal_cooldown: i64, // seconds deposits and escrowed shares wait before they settle
+        lp_fee_bps: u16,          // vault's cut of maker fees on markets it backs
+    ) -> Result<()> {
         // Validations
-        require!(bet.is_resolved, BettingError::BetNotResolved);
         require!(
-            bet.creator == *ctx.accounts.creator.key,
-            BettingError::UnauthorizedResolver
+            ctx.accounts.platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+        require!(
+            withdrawal_cooldown >= 0 && lp_fee_bps <= 10000,
+            BettingError::InvalidPlatformConfig
         );
-        require!(bet.maker_fee_collected > 0, BettingError::NoFeesToClaim);
 
-        let fees_to_claim = bet.maker_fee_collected;
+        let house_vault = &mut ctx.accounts.house_vault;
+        house_vault.lp_mint = ctx.accounts.lp_mint.key();
+        house_vault.withdrawal_cooldown = withdrawal_cooldown;
+        house_vault.lp_fee_bps = lp_fee_bps;
+        house_vault.bump = ctx.bumps.house_vault;
 
-        // Transfer fees to creator
-        **bet.to_account_info().try_borrow_mut_lamports()? -= fees_to_claim;
-        **ctx
-            .accounts
-            .creator
-            .to_account_info()
-            .try_borrow_mut_lamports()? += fees_to_claim;
+        msg!("Liquidity vault configured");
+        Ok(())
+    }
 
-        bet.maker_fee_collected = 0;
+    /// Deposit SOL into the house vault, starting the cooldown after which it is
+    /// priced into LP shares
+    pub fn deposit_liquidity(ctx: Context<DepositLiquidity>, amount: u64) -> Result<()> {
+        // Validations
+        require!(amount > 0, BettingError::InvalidAmount);
+        require!(
+            calculate_lp_shares(
+                amount,
+                ctx.accounts.lp_mint.supply,
+                vault_net_asset_value(&ctx.accounts.house_vault)?,
+            ) > 0,
+            BettingError::InvalidAmount
+        );
 
-        msg!("Maker fees claimed: {} SOL", fees_to_claim);
+        // Transfer SOL from provider to the house vault
+        let transfer_instruction = system_program::Transfer {
+            from: ctx.accounts.provider.to_account_info(),
+            to: ctx.accounts.house_vault.to_account_info(),
+        };
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                transfer_instruction,
+            ),
+            amount,
+        )?;
+
+        // Pending deposits stay out of the NAV so they can't buy into gains already decided
+        ctx.accounts.house_vault.total_pending_deposits += amount;
+
+        let deposit_request = &mut ctx.accounts.deposit_request;
+        deposit_request.provider = *ctx.accounts.provider.key;
+        deposit_request.amount = amount;
+        deposit_request.requested_at = Clock::get()?.unix_timestamp;
+        deposit_request.bump = ctx.bumps.deposit_request;
+
+        msg!("Liquidity deposit requested: {} SOL", amount);
         Ok(())
     }
 
-    /// Claim winnings for a winning bet
-    pub fn claim_winnings(ctx: Context<ClaimWinnings>, _bet_id: String) -> Result<()> {
-        let bet = &mut ctx.accounts.bet;
-        let user_bet = &mut ctx.accounts.user_bet;
+    /// Mint LP shares for a deposit at the NAV once its cooldown has passed (anyone can do this)
+    pub fn claim_deposit_shares(ctx: Context<ClaimDepositShares>) -> Result<()> {
+        let amount = ctx.accounts.deposit_request.amount;
 
         // Validations
-        require!(bet.is_resolved, BettingError::BetNotResolved);
-        require!(!user_bet.is_claimed, BettingError::AlreadyClaimed);
         require!(
-            user_bet.option == bet.winning_option,
-            BettingError::NotWinner
+            Clock::get()?.unix_timestamp
+                >= ctx.accounts.deposit_request.requested_at
+                    + ctx.accounts.house_vault.withdrawal_cooldown,
+            BettingError::DepositCooldownActive
         );
 
-        let total_winning_pool = if bet.winning_option == 1 {
-            bet.total_amount_a
-        } else {
-            bet.total_amount_b
-        };
+        let shares = calculate_lp_shares(
+            amount,
+            ctx.accounts.lp_mint.supply,
+            vault_net_asset_value(&ctx.accounts.house_vault)?,
+        );
+        ctx.accounts.house_vault.total_pending_deposits -= amount;
 
-        require!(total_winning_pool > 0, BettingError::NoWinnersFound);
+        // Mint the provider's shares, signed by the vault
+        let house_vault = &ctx.accounts.house_vault;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"house_vault", &[house_vault.bump]]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.provider_lp_account.to_account_info(),
+                    authority: house_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares,
+        )?;
 
-        let total_pool = bet.total_amount_a + bet.total_amount_b;
+        msg!("Liquidity deposited: {} SOL for {} shares", amount, shares);
+        Ok(())
+    }
 
-        // Calculate winnings proportionally
-        let winnings = calculate_winnings(user_bet.amount, total_winning_pool, total_pool)?;
+    /// Move LP shares into escrow to start the withdrawal cooldown, the shares stay
+    /// exposed to the vault's results until they are redeemed
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, shares: u64) -> Result<()> {
+        // Validations
+        require!(shares > 0, BettingError::InvalidAmount);
 
-        // Transfer winnings to user
-        **bet.to_account_info().try_borrow_mut_lamports()? -= winnings;
-        **ctx
-            .accounts
-            .user
-            .to_account_info()
-            .try_borrow_mut_lamports()? += winnings;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.provider_lp_account.to_account_info(),
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.lp_escrow.to_account_info(),
+                    authority: ctx.accounts.provider.to_account_info(),
+                },
+            ),
+            shares,
+            ctx.accounts.lp_mint.decimals,
+        )?;
 
-        user_bet.is_claimed = true;
-        user_bet.claimed_at = Clock::get()?.unix_timestamp;
+        let withdrawal_request = &mut ctx.accounts.withdrawal_request;
+        withdrawal_request.provider = *ctx.accounts.provider.key;
+        withdrawal_request.shares = shares;
+        withdrawal_request.requested_at = Clock::get()?.unix_timestamp;
+        withdrawal_request.bump = ctx.bumps.withdrawal_request;
 
-        msg!("Winnings claimed: {} SOL", winnings);
+        msg!("Withdrawal requested: {} shares", shares);
         Ok(())
     }
 
-    /// Cancel bet (only creator can do this, only if no bets placed)
-    pub fn cancel_bet(ctx: Context<CancelBet>, _bet_id: String) -> Result<()> {
-        let bet = &ctx.accounts.bet;
+    /// Burn escrowed LP shares for their share of the house vault's NAV once the cooldown has passed
+    pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>) -> Result<()> {
+        let house_vault = &ctx.accounts.house_vault;
+        let shares = ctx.accounts.withdrawal_request.shares;
 
         // Validations
         require!(
-            bet.creator == *ctx.accounts.creator.key,
-            BettingError::UnauthorizedResolver
+            Clock::get()?.unix_timestamp
+                >= ctx.accounts.withdrawal_request.requested_at + house_vault.withdrawal_cooldown,
+            BettingError::WithdrawalCooldownActive
+        );
+
+        let amount = calculate_lp_redemption(
+            shares,
+            ctx.accounts.lp_mint.supply,
+            vault_net_asset_value(house_vault)?,
         );
         require!(
-            bet.total_amount_a == 0 && bet.total_amount_b == 0,
-            BettingError::BetsAlreadyPlaced
+            amount <= vault_free_liquidity(house_vault)?,
+            BettingError::InsufficientVaultLiquidity
         );
-        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
 
-        msg!("Bet cancelled: {}", bet.bet_id);
+        // Burn the escrowed shares and return the escrow's rent, signed by the vault
+        let signer_seeds: &[&[&[u8]]] = &[&[b"house_vault", &[house_vault.bump]]];
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.lp_escrow.to_account_info(),
+                    authority: house_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            shares,
+        )?;
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.lp_escrow.to_account_info(),
+                destination: ctx.accounts.provider.to_account_info(),
+                authority: house_vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        // Transfer SOL from the house vault to provider
+        **house_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .provider
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        msg!("Liquidity withdrawn: {} shares for {} SOL", shares, amount);
         Ok(())
     }
 
-    /// Get bet statistics
-    pub fn get_bet_stats(ctx: Context<GetBetStats>, _bet_id: String) -> Result<BetStats> {
-        let bet = &ctx.accounts.bet;
+    /// Allow a wallet to create markets in permissioned mode (only platform owner can do this)
+    pub fn approve_creator(
+        ctx: Context<ApproveCreator>,
+        creator: Pubkey,
+        expires_at: i64, // 0 = never expires
+    ) -> Result<()> {
+        let creator_approval = &mut ctx.accounts.creator_approval;
         let clock = Clock::get()?;
 
-        let total_pool = bet.total_amount_a + bet.total_amount_b;
-        let time_remaining = if bet.end_time > clock.unix_timestamp {
-            bet.end_time - clock.unix_timestamp
-        } else {
-            0
-        };
+        // Validations
+        require!(
+            ctx.accounts.platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+        require!(
+            expires_at == 0 || expires_at > clock.unix_timestamp,
+            BettingError::InvalidExpiry
+        );
 
-        let (odds_a, odds_b) = calculate_odds(bet.total_amount_a, bet.total_amount_b);
+        creator_approval.creator = creator;
+        creator_approval.approved_by = *ctx.accounts.owner.key;
+        creator_approval.approved_at = clock.unix_timestamp;
+        creator_approval.expires_at = expires_at;
+        creator_approval.is_revoked = false;
+        creator_approval.bump = ctx.bumps.creator_approval;
 
-        Ok(BetStats {
-            total_pool,
-            odds_a,
-            odds_b,
-            total_bettors: bet.total_bettors,
-            time_remaining,
-        })
+        msg!("Creator approved: {}", creator);
+        Ok(())
     }
-}
 
-// Helper functions
-fn calculate_maker_fee(amount: u64) -> u64 {
-    (amount * MAKER_FEE_RATE) / 10000
-}
+    /// Revoke a creator's approval (only platform owner can do this)
+    pub fn revoke_creator_approval(ctx: Context<RevokeCreatorApproval>) -> Result<()> {
+        let creator_approval = &mut ctx.accounts.creator_approval;
 
-fn calculate_winnings(user_amount: u64, total_winning_pool: u64, total_pool: u64) -> Result<u64> {
-    require!(total_winning_pool > 0, BettingError::NoWinnersFound);
+        require!(
+            ctx.accounts.platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
 
-    let winnings = (user_amount as u128 * total_pool as u128) / total_winning_pool as u128;
-    Ok(winnings as u64)
-}
+        creator_approval.is_revoked = true;
 
-fn calculate_odds(amount_a: u64, amount_b: u64) -> (u64, u64) {
-    let total = amount_a + amount_b;
-    if total == 0 {
-        return (5000, 5000); // 50/50 odds in basis points
+        msg!("Creator approval revoked: {}", creator_approval.creator);
+        Ok(())
     }
 
-    let odds_a = (amount_b * 10000) / total;
+    /// Register a market category (only platform owner can do this)
+    pub fn create_category(
+        ctx: Context<CreateCategory>,
+        category_id: u32,
+        name: String,
+        default_maker_fee_bps: u16,
+        default_min_bet_amount: u64,
+        default_max_bet_amount: u64,
+    ) -> Result<()> {
+        let category = &mut ctx.accounts.category;
+        let platform_config = &ctx.accounts.platform_config;
+
+        // Validations
+        require!(
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+        validate_category_settings(
+            platform_config,
+            &name,
+            default_maker_fee_bps,
+            default_min_bet_amount,
+            default_max_bet_amount,
+        )?;
+
+        category.id = category_id;
+        category.name = name;
+        category.enabled = true;
+        category.default_maker_fee_bps = default_maker_fee_bps;
+        category.default_min_bet_amount = default_min_bet_amount;
+        category.default_max_bet_amount = default_max_bet_amount;
+        category.market_count = 0;
+        category.total_volume = 0;
+        category.created_at = Clock::get()?.unix_timestamp;
+        category.bump = ctx.bumps.category;
+
+        msg!("Category created: {}", category.name);
+        Ok(())
+    }
+
+    /// Rename, enable/disable or change the defaults of a category (only platform owner can do this)
+    pub fn update_category(
+        ctx: Context<UpdateCategory>,
+        name: String,
+        enabled: bool,
+        default_maker_fee_bps: u16,
+        default_min_bet_amount: u64,
+        default_max_bet_amount: u64,
+    ) -> Result<()> {
+        let category = &mut ctx.accounts.category;
+        let platform_config = &ctx.accounts.platform_config;
+
+        // Validations
+        require!(
+            platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+        validate_category_settings(
+            platform_config,
+            &name,
+            default_maker_fee_bps,
+            default_min_bet_amount,
+            default_max_bet_amount,
+        )?;
+
+        category.name = name;
+        category.enabled = enabled;
+        category.default_maker_fee_bps = default_maker_fee_bps;
+        category.default_min_bet_amount = default_min_bet_amount;
+        category.default_max_bet_amount = default_max_bet_amount;
+
+        msg!("Category updated: {}", category.name);
+        Ok(())
+    }
+
+    /// Create a new betting market
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_bet(
+        ctx: Context<CreateBet>,
+        bet_id: String,
+        description: String,
+        option_a: String,
+        option_b: String,
+        start_time: i64, // values in the past open the market immediately
+        end_time: i64,
+        min_bet_amount: Option<u64>, // defaults to the category's limits
+        max_bet_amount: Option<u64>,
+        maker_fee_bps: Option<u16>, // defaults to the category's fee
+        creator_bond: u64,          // lamports returned after a clean resolution, slashed otherwise
+        access_requirement: AccessRequirement,
+        limits: MarketLimits,
+        anti_sniping: AntiSnipingConfig,
+        commit_reveal: Option<CommitRevealConfig>, // None = bets are public
+        market_type: MarketType,
+        bucket_bounds: Vec<i64>, // ascending bucket edges, empty unless bucketed
+        parent_outcome: u8,      // 0 unless a parent market account is passed
+        refund_fees_on_draw: bool,
+        early_bonus: EarlyBonus,
+        fee_curve: FeeCurve,
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let category = &mut ctx.accounts.category;
+        let platform_config = &ctx.accounts.platform_config;
+        let clock = Clock::get()?;
+
+        if platform_config.permissioned_creation {
+            let creator_approval = ctx
+                .accounts
+                .creator_approval
+                .as_ref()
+                .ok_or(BettingError::CreatorNotApproved)?;
+            require!(
+                !creator_approval.is_revoked,
+                BettingError::CreatorNotApproved
+            );
+            require!(
+                creator_approval.expires_at == 0
+                    || clock.unix_timestamp < creator_approval.expires_at,
+                BettingError::CreatorApprovalExpired
+            );
+        }
+
+        let start_time = start_time.max(clock.unix_timestamp);
+        let min_bet_amount = min_bet_amount.unwrap_or(category.default_min_bet_amount);
+        let max_bet_amount = max_bet_amount.unwrap_or(category.default_max_bet_amount);
+        let maker_fee_bps = maker_fee_bps.unwrap_or(category.default_maker_fee_bps);
+
+        // Validations
+        let earliest_end_time = start_time
+            .checked_add(MIN_BET_DURATION)
+            .ok_or(BettingError::InvalidEndTime)?;
+        require!(end_time > earliest_end_time, BettingError::InvalidEndTime);
+        require!(min_bet_amount > 0, BettingError::InvalidAmount);
+        require!(
+            max_bet_amount >= min_bet_amount,
+            BettingError::InvalidAmount
+        );
+        require!(
+            limits.max_position_per_user == 0 || limits.max_position_per_user >= min_bet_amount,
+            BettingError::InvalidAmount
+        );
+        require!(
+            limits.max_total_pool == 0 || limits.max_total_pool >= min_bet_amount,
+            BettingError::InvalidAmount
+        );
+        require!(
+            limits.max_imbalance_bps == 0 || limits.max_imbalance_bps >= 10000,
+            BettingError::InvalidMarketLimits
+        );
+        require!(
+            (0..end_time - start_time).contains(&anti_sniping.cutoff_window)
+                && (0..end_time - start_time).contains(&anti_sniping.random_close_window),
+            BettingError::InvalidAntiSnipingConfig
+        );
+        if let Some(commit_reveal) = &commit_reveal {
+            require!(
+                commit_reveal.reveal_window > 0 && anti_sniping.random_close_window == 0,
+                BettingError::InvalidCommitRevealConfig
+            );
+        }
+        if let MarketType::Scalar {
+            lower_bound,
+            upper_bound,
+        } = market_type
+        {
+            require!(lower_bound < upper_bound, BettingError::InvalidScalarBounds);
+        }
+        if let MarketType::Bucketed { .. } = market_type {
+            require!(
+                (3..=MAX_BUCKETS + 1).contains(&bucket_bounds.len())
+                    && bucket_bounds.windows(2).all(|edges| edges[0] < edges[1]),
+                BettingError::InvalidBucketBounds
+            );
+            // Late-stake tracking and the imbalance limit only cover two-sided markets
+            require!(
+                anti_sniping.random_close_window == 0,
+                BettingError::InvalidAntiSnipingConfig
+            );
+            require!(
+                limits.max_imbalance_bps == 0,
+                BettingError::InvalidMarketLimits
+            );
+        } else {
+            require!(bucket_bounds.is_empty(), BettingError::InvalidBucketBounds);
+        }
+        if let MarketType::FixedOdds { bookmaker } = market_type {
+            // House capital is only put behind the platform's approved bookmaker
+            require!(
+                bookmaker != Pubkey::default()
+                    && bookmaker == ctx.accounts.platform_config.bookmaker
+                    && bookmaker == *ctx.accounts.creator.key,
+                BettingError::UnauthorizedBookmaker
+            );
+            // Locked prices leave nothing to hide, exclude or rebalance
+            require!(
+                commit_reveal.is_none()
+                    && anti_sniping.random_close_window == 0
+                    && limits.max_imbalance_bps == 0,
+                BettingError::InvalidFixedOddsConfig
+            );
+        }
+        match early_bonus {
+            EarlyBonus::None => {}
+            EarlyBonus::Linear { max_bonus_bps }
+            | EarlyBonus::Exponential { max_bonus_bps, .. } => {
+                // Excluded late stakes and locked prices don't fit weighted pool shares
+                require!(
+                    max_bonus_bps <= 10000
+                        && anti_sniping.random_close_window == 0
+                        && !matches!(market_type, MarketType::FixedOdds { .. }),
+                    BettingError::InvalidEarlyBonus
+                );
+            }
+        }
+        if let EarlyBonus::Exponential { half_life, .. } = early_bonus {
+            require!(half_life > 0, BettingError::InvalidEarlyBonus);
+        }
+        match fee_curve {
+            FeeCurve::Flat => {}
+            FeeCurve::TimeToClose { max_extra_fee_bps } => {
+                require!(
+                    max_extra_fee_bps as u64 <= MAX_FEE_RATE,
+                    BettingError::InvalidFeeCurve
+                );
+            }
+            FeeCurve::Imbalance { max_extra_fee_bps } => {
+                // Imbalance is measured between two visible sides
+                require!(
+                    max_extra_fee_bps as u64 <= MAX_FEE_RATE
+                        && commit_reveal.is_none()
+                        && !matches!(market_type, MarketType::Bucketed { .. }),
+                    BettingError::InvalidFeeCurve
+                );
+            }
+        }
+        let parent_market = match &mut ctx.accounts.parent_market {
+            Some(parent_market) => {
+                require!(
+                    !parent_market.is_resolved
+                        && !parent_market.is_voided
+                        && !matches!(parent_market.market_type, MarketType::Scalar { .. })
+                        && is_valid_option(parent_market, parent_outcome),
+                    BettingError::InvalidParentMarket
+                );
+                // A parent with children can no longer be cancelled out from under them
+                parent_market.child_count += 1;
+                parent_market.key()
+            }
+            None => {
+                require!(parent_outcome == 0, BettingError::InvalidParentMarket);
+                Pubkey::default()
+            }
+        };
+        if let CutoffMode::FeeRamp { max_extra_fee_bps } = anti_sniping.cutoff_mode {
+            require!(
+                max_extra_fee_bps as u64 <= MAX_FEE_RATE,
+                BettingError::FeeTooHigh
+            );
+        }
+        require!(category.enabled, BettingError::CategoryDisabled);
+        require!(
+            !description.is_empty() && description.len() <= MAX_STRING_LENGTH,
+            BettingError::InvalidDescription
+        );
+        require!(
+            maker_fee_bps as u64 <= MAX_FEE_RATE
+                && maker_fee_bps <= platform_config.max_maker_fee_bps,
+            BettingError::FeeTooHigh
+        );
+        require!(
+            creator_bond >= platform_config.min_creator_bond,
+            BettingError::BondTooLow
+        );
+
+        // Hold the creator's bond in the bet account
+        if creator_bond > 0 {
+            let transfer_instruction = system_program::Transfer {
+                from: ctx.accounts.creator.to_account_info(),
+                to: bet.to_account_info(),
+            };
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    transfer_instruction,
+                ),
+                creator_bond,
+            )?;
+        }
+
+        // Initialize bet state
+        bet.creator = *ctx.accounts.creator.key;
+        bet.bet_id = bet_id;
+        bet.description = description;
+        bet.option_a = option_a;
+        bet.option_b = option_b;
+        bet.start_time = start_time;
+        bet.end_time = end_time;
+        bet.original_end_time = end_time;
+        bet.total_amount_a = 0;
+        bet.total_amount_b = 0;
+        bet.is_resolved = false;
+        bet.winning_option = 0; // 0 = unresolved, 1 = option A, 2 = option B, DRAW_OUTCOME = draw
+        bet.is_overturned = false;
+        bet.is_voided = false;
+        bet.bump = ctx.bumps.bet;
+        bet.min_bet_amount = min_bet_amount;
+        bet.max_bet_amount = max_bet_amount;
+        bet.category = category.key();
+        bet.created_at = clock.unix_timestamp;
+        bet.resolved_at = 0;
+        bet.maker_fee_bps = maker_fee_bps;
+        bet.maker_fee_collected = 0;
+        bet.creator_bond = creator_bond;
+        bet.slashed_amount = 0;
+        bet.access_requirement = access_requirement;
+        bet.max_position_per_user = limits.max_position_per_user;
+        bet.max_total_pool = limits.max_total_pool;
+        bet.max_imbalance_bps = limits.max_imbalance_bps;
+        bet.cutoff_window = anti_sniping.cutoff_window;
+        bet.cutoff_mode = anti_sniping.cutoff_mode;
+        bet.random_close_window = anti_sniping.random_close_window;
+        bet.late_amounts_a = [0; RANDOM_CLOSE_SLICES];
+        bet.late_amounts_b = [0; RANDOM_CLOSE_SLICES];
+        bet.effective_end_time = 0;
+        bet.random_close_seed_slot = 0;
+        bet.reveal_window = commit_reveal
+            .as_ref()
+            .map_or(0, |config| config.reveal_window);
+        bet.forfeit_unrevealed = commit_reveal.is_some_and(|config| config.forfeit_unrevealed);
+        bet.total_committed = 0;
+        bet.total_bettors = 0;
+        bet.market_type = market_type;
+        bet.resolved_value = 0;
+        bet.bucket_amounts = vec![0; bucket_bounds.len().saturating_sub(1)];
+        bet.bucket_bounds = bucket_bounds;
+        bet.parent_market = parent_market;
+        bet.parent_outcome = parent_outcome;
+        bet.parent_condition_met = false;
+        bet.parent_resolved_at = 0;
+        bet.child_count = 0;
+        bet.fixed_odds_a_bps = 0;
+        bet.fixed_odds_b_bps = 0;
+        bet.liability_a = 0;
+        bet.liability_b = 0;
+        bet.house_reserve = 0;
+        bet.house_settled = false;
+        bet.house_reserve_mark = 0;
+        bet.parlay_eligible = false;
+        bet.refund_fees_on_draw = refund_fees_on_draw;
+        bet.resolution_weights_bps = Vec::new();
+        bet.early_bonus = early_bonus;
+        bet.fee_curve = fee_curve;
+        bet.weighted_pools = if early_bonus == EarlyBonus::None {
+            Vec::new()
+        } else {
+            vec![0; option_count(bet)]
+        };
+        bet.result_details = String::new();
+
+        // Update the creator's track record, creating it on their first market
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        if creator_profile.creator == Pubkey::default() {
+            creator_profile.creator = *ctx.accounts.creator.key;
+            creator_profile.created_at = clock.unix_timestamp;
+            creator_profile.bump = ctx.bumps.creator_profile;
+        }
+        creator_profile.markets_created += 1;
+
+        category.market_count += 1;
+
+        msg!("Bet created: {}", bet.bet_id);
+        Ok(())
+    }
+
+    /// Place a bet on an option
+    pub fn place_bet(
+        ctx: Context<PlaceBet>,
+        bet_id: String,
+        option: u8, // 1 for option A, 2 for option B, ignored for commit-reveal markets
+        amount: u64,
+        commitment: Option<[u8; 32]>, // sha256(option, salt, user) for commit-reveal markets
+        allowlist_proof: Vec<[u8; 32]>, // only read by Merkle-allowlisted markets
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        verify_bettor_access(ctx.accounts, &allowlist_proof, clock.unix_timestamp)?;
+
+        let bet = &mut ctx.accounts.bet;
+        let user_bet = &mut ctx.accounts.user_bet;
+
+        // Validations
+        require!(
+            clock.unix_timestamp >= bet.start_time,
+            BettingError::BettingNotOpen
+        );
+        require!(
+            clock.unix_timestamp < bet.end_time,
+            BettingError::BettingClosed
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        // Conditional markets can be voided by their parent before they close
+        require!(!bet.is_voided, BettingError::BetVoided);
+        let is_commit_reveal = bet.reveal_window > 0;
+        require!(
+            !is_commit_reveal || commitment.is_some(),
+            BettingError::CommitmentRequired
+        );
+        require!(
+            is_commit_reveal || commitment.is_none(),
+            BettingError::NotCommitReveal
+        );
+        // Committed stakes stay on the hidden side (option 0) until revealed
+        let option = if is_commit_reveal { 0 } else { option };
+        require!(
+            is_commit_reveal || is_valid_option(bet, option),
+            BettingError::InvalidOption
+        );
+        require!(amount > 0, BettingError::InvalidAmount);
+        require!(amount >= bet.min_bet_amount, BettingError::BetTooLow);
+        require!(amount <= bet.max_bet_amount, BettingError::BetTooHigh);
+        require!(
+            bet.cutoff_mode != CutoffMode::BlockBets
+                || bet.cutoff_window == 0
+                || clock.unix_timestamp < bet.end_time - bet.cutoff_window,
+            BettingError::CutoffWindowActive
+        );
+
+        // Top-ups must stay on the same side and within the position and exposure caps
+        let is_new_position = user_bet.user == Pubkey::default();
+        require!(
+            is_new_position || user_bet.option == option,
+            BettingError::PositionOptionMismatch
+        );
+        let commitment = commitment.unwrap_or_default();
+        require!(
+            is_new_position || user_bet.commitment == commitment,
+            BettingError::CommitmentMismatch
+        );
+        let late_slice = random_close_slice(bet, clock.unix_timestamp);
+        require!(
+            is_new_position || late_slice.is_none(),
+            BettingError::RandomCloseWindowActive
+        );
+        require!(
+            bet.max_position_per_user == 0
+                || user_bet.original_amount + amount <= bet.max_position_per_user,
+            BettingError::PositionLimitExceeded
+        );
+        let user_profile = &mut ctx.accounts.user_profile;
+        let max_open_exposure = ctx.accounts.platform_config.max_open_exposure_per_user;
+        require!(
+            max_open_exposure == 0 || user_profile.open_exposure + amount <= max_open_exposure,
+            BettingError::ExposureLimitExceeded
+        );
+
+        // Calculate maker fee from the user's volume tier, the market's fee curve
+        // and the referrer's cut of it
+        let (fee_bps, _) = calculate_fee_bps(
+            bet,
+            &ctx.accounts.platform_config.fee_tiers,
+            user_profile.total_wagered,
+            option,
+            amount,
+            clock.unix_timestamp,
+        );
+        let maker_fee = calculate_maker_fee(amount, fee_bps);
+        let net_amount = amount - maker_fee;
+
+        // Fixed-odds stakes lock the bookmaker's current price
+        let is_fixed_odds = matches!(bet.market_type, MarketType::FixedOdds { .. });
+        let locked_odds_bps = if option == 1 {
+            bet.fixed_odds_a_bps
+        } else {
+            bet.fixed_odds_b_bps
+        };
+        require!(
+            !is_fixed_odds || locked_odds_bps > 0,
+            BettingError::OddsNotOffered
+        );
+        require!(
+            !is_fixed_odds || ctx.accounts.house_vault.is_some(),
+            BettingError::HouseVaultRequired
+        );
+        let potential_payout = if is_fixed_odds {
+            (net_amount as u128 * locked_odds_bps as u128 / 10000) as u64
+        } else {
+            0
+        };
+        let referral_reward = match &ctx.accounts.referral {
+            Some(referral) => {
+                require!(
+                    referral.referrer != *ctx.accounts.user.key,
+                    BettingError::SelfReferral
+                );
+                calculate_referral_reward(maker_fee, ctx.accounts.platform_config.referral_fee_bps)
+            }
+            None => 0,
+        };
+        // The house vault earns a cut of the remaining fee on markets it backs
+        let lp_fee = match &ctx.accounts.house_vault {
+            Some(house_vault) if is_fixed_odds => {
+                (maker_fee - referral_reward) * house_vault.lp_fee_bps as u64 / 10000
+            }
+            _ => 0,
+        };
+
+        // Update bet totals, remembering in-window stakes in case the random close excludes them
+        *option_pool_mut(bet, option) += net_amount;
+        let weighted_amount =
+            calculate_early_weighted_amount(bet, net_amount, clock.unix_timestamp);
+        if option != 0 && !bet.weighted_pools.is_empty() {
+            bet.weighted_pools[option as usize - 1] += weighted_amount;
+        }
+        if is_fixed_odds {
+            if option == 1 {
+                bet.liability_a += potential_payout;
+            } else {
+                bet.liability_b += potential_payout;
+            }
+
+            // Pull in house capital so the market can always cover its worst outcome
+            let required_reserve = bet
+                .liability_a
+                .max(bet.liability_b)
+                .saturating_sub(total_pool(bet));
+            if required_reserve > bet.house_reserve {
+                let house_vault = ctx
+                    .accounts
+                    .house_vault
+                    .as_mut()
+                    .ok_or(BettingError::HouseVaultRequired)?;
+                let top_up = required_reserve - bet.house_reserve;
+                require!(
+                    top_up <= vault_free_liquidity(house_vault)?,
+                    BettingError::InsufficientVaultLiquidity
+                );
+                require!(
+                    required_reserve as u128
+                        <= vault_net_asset_value(house_vault)? as u128
+                            * ctx.accounts.platform_config.max_market_reserve_bps as u128
+                            / 10000,
+                    BettingError::MarketReserveCapExceeded
+                );
+
+                **house_vault.to_account_info().try_borrow_mut_lamports()? -= top_up;
+                **bet.to_account_info().try_borrow_mut_lamports()? += top_up;
+
+                house_vault.total_market_reserves += top_up;
+                bet.house_reserve = required_reserve;
+                bet.house_reserve_mark += top_up;
+            }
+        }
+        if let Some(slice) = late_slice {
+            if option == 1 {
+                bet.late_amounts_a[slice] += net_amount;
+            } else {
+                bet.late_amounts_b[slice] += net_amount;
+            }
+        }
+
+        // Keep the pool within the creator's size and balance limits
+        require!(
+            bet.max_total_pool == 0 || total_pool(bet) + bet.total_committed <= bet.max_total_pool,
+            BettingError::PoolCapExceeded
+        );
+        let (bet_side, other_side) = if option == 1 {
+            (bet.total_amount_a, bet.total_amount_b)
+        } else {
+            (bet.total_amount_b, bet.total_amount_a)
+        };
+        require!(
+            bet.max_imbalance_bps == 0
+                || is_commit_reveal
+                || bet_side <= other_side
+                || bet_side as u128 * 10000
+                    <= bet.max_imbalance_bps as u128 * other_side.max(bet.min_bet_amount) as u128,
+            BettingError::PoolImbalanceExceeded
+        );
+
+        if is_new_position {
+            bet.total_bettors += 1;
+        }
+        bet.maker_fee_collected += maker_fee - referral_reward - lp_fee;
+
+        // Update the user's profile, creating it on their first bet
+        if user_profile.user == Pubkey::default() {
+            user_profile.user = *ctx.accounts.user.key;
+            user_profile.created_at = clock.unix_timestamp;
+            user_profile.bump = ctx.bumps.user_profile;
+        }
+        user_profile.total_wagered += amount;
+        user_profile.open_exposure += amount;
+        if is_new_position {
+            user_profile.markets_entered += 1;
+            user_profile.open_positions += 1;
+        }
+
+        ctx.accounts.creator_profile.total_volume_hosted += amount;
+        ctx.accounts.category.total_volume += amount;
+
+        // Transfer SOL from user to bet account
+        let transfer_instruction = system_program::Transfer {
+            from: ctx.accounts.user.to_account_info(),
+            to: ctx.accounts.bet.to_account_info(),
+        };
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                transfer_instruction,
+            ),
+            amount - referral_reward,
+        )?;
+
+        if let Some(house_vault) = ctx.accounts.house_vault.as_mut() {
+            if lp_fee > 0 {
+                **ctx
+                    .accounts
+                    .bet
+                    .to_account_info()
+                    .try_borrow_mut_lamports()? -= lp_fee;
+                **house_vault.to_account_info().try_borrow_mut_lamports()? += lp_fee;
+                house_vault.total_fees_earned += lp_fee;
+            }
+        }
+
+        // Accrue the referrer's cut in their referral account
+        if let Some(referral) = ctx.accounts.referral.as_mut() {
+            referral.accrued_rewards += referral_reward;
+            referral.total_earned += referral_reward;
+            referral.referred_bets += 1;
+            referral.referred_volume += amount;
+
+            if referral_reward > 0 {
+                let transfer_instruction = system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: referral.to_account_info(),
+                };
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        transfer_instruction,
+                    ),
+                    referral_reward,
+                )?;
+            }
+        }
+
+        // Store user's bet, or add to it on a top-up
+        if is_new_position {
+            user_bet.user = *ctx.accounts.user.key;
+            user_bet.bet_id = bet_id;
+            user_bet.option = option;
+            user_bet.is_claimed = false;
+            user_bet.bump = ctx.bumps.user_bet;
+            user_bet.placed_at = clock.unix_timestamp;
+            user_bet.claimed_at = 0;
+            user_bet.late_slice = late_slice.map_or(0, |slice| slice as u8 + 1);
+            user_bet.commitment = commitment;
+        }
+        user_bet.amount += net_amount;
+        user_bet.weighted_amount += weighted_amount;
+        user_bet.fee_bps = fee_bps;
+        user_bet.fees_paid += maker_fee;
+        user_bet.original_amount += amount;
+        if is_fixed_odds {
+            // Top-ups at a new price leave the position at its average odds
+            user_bet.potential_payout += potential_payout;
+            user_bet.locked_odds_bps =
+                (user_bet.potential_payout as u128 * 10000 / user_bet.amount as u128) as u64;
+        }
+
+        msg!("Bet placed: {} SOL on option {}", amount, option);
+        Ok(())
+    }
+
+    /// Reveal a committed bet, moving its stake onto the chosen option
+    pub fn reveal_bet(
+        ctx: Context<RevealBet>,
+        _bet_id: String,
+        option: u8,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let user_bet = &mut ctx.accounts.user_bet;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(bet.reveal_window > 0, BettingError::NotCommitReveal);
+        require!(
+            clock.unix_timestamp >= bet.end_time,
+            BettingError::BettingStillOpen
+        );
+        require!(
+            clock.unix_timestamp < bet.end_time + bet.reveal_window,
+            BettingError::RevealWindowClosed
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(!bet.is_voided, BettingError::BetVoided);
+        require!(user_bet.option == 0, BettingError::AlreadyRevealed);
+        require!(is_valid_option(bet, option), BettingError::InvalidOption);
+        require!(
+            hashv(&[&[option], &salt, ctx.accounts.user.key.as_ref()]).to_bytes()
+                == user_bet.commitment,
+            BettingError::CommitmentMismatch
+        );
+
+        bet.total_committed -= user_bet.amount;
+        *option_pool_mut(bet, option) += user_bet.amount;
+        if !bet.weighted_pools.is_empty() {
+            bet.weighted_pools[option as usize - 1] += user_bet.weighted_amount;
+        }
+        user_bet.option = option;
+
+        msg!("Bet revealed: option {}", option);
+        Ok(())
+    }
+
+    /// Register the signer as a referrer so bettors can name them in `place_bet`
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+
+        referral.referrer = *ctx.accounts.referrer.key;
+        referral.accrued_rewards = 0;
+        referral.total_earned = 0;
+        referral.total_claimed = 0;
+        referral.referred_bets = 0;
+        referral.referred_volume = 0;
+        referral.created_at = Clock::get()?.unix_timestamp;
+        referral.bump = ctx.bumps.referral;
+
+        msg!("Referrer registered: {}", referral.referrer);
+        Ok(())
+    }
+
+    /// Claim accrued referral rewards (only the referrer can do this)
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+
+        // Validations
+        require!(
+            referral.referrer == *ctx.accounts.referrer.key,
+            BettingError::UnauthorizedReferrer
+        );
+        require!(referral.accrued_rewards > 0, BettingError::NoFeesToClaim);
+
+        let rewards_to_claim = referral.accrued_rewards;
+
+        // Transfer rewards to referrer
+        **referral.to_account_info().try_borrow_mut_lamports()? -= rewards_to_claim;
+        **ctx
+            .accounts
+            .referrer
+            .to_account_info()
+            .try_borrow_mut_lamports()? += rewards_to_claim;
+
+        referral.accrued_rewards = 0;
+        referral.total_claimed += rewards_to_claim;
+
+        msg!("Referral rewards claimed: {} SOL", rewards_to_claim);
+        Ok(())
+    }
+
+    /// Attest that `subject` may enter markets gated on the signer's attestations
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        subject: Pubkey,
+        expires_at: i64, // 0 = never expires
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        let clock = Clock::get()?;
+
+        require!(
+            expires_at == 0 || expires_at > clock.unix_timestamp,
+            BettingError::InvalidExpiry
+        );
+
+        attestation.issuer = *ctx.accounts.issuer.key;
+        attestation.subject = subject;
+        attestation.issued_at = clock.unix_timestamp;
+        attestation.expires_at = expires_at;
+        attestation.is_revoked = false;
+        attestation.bump = ctx.bumps.attestation;
+
+        msg!("Attestation issued for {}", subject);
+        Ok(())
+    }
+
+    /// Revoke an attestation (only its issuer can do this)
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+
+        attestation.is_revoked = true;
+
+        msg!("Attestation revoked for {}", attestation.subject);
+        Ok(())
+    }
+
+    /// Resolve a bet (only creator can do this, or the platform arbiter on fixed-odds markets)
+    pub fn resolve_bet(
+        ctx: Context<ResolveBet>,
+        _bet_id: String,
+        winning_option: u8, // ignored for scalar, line and bucketed markets
+        result_details: String,
+        resolved_value: Option<i64>, // required for scalar, line and bucketed markets
+        weights_bps: Vec<u16>,       // per-option payout weights for a split resolution, or empty
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(!bet.is_voided, BettingError::BetVoided);
+        let (winning_option, resolved_value) =
+            validate_resolution(bet, winning_option, resolved_value, &weights_bps)?;
+        // House-backed markets are resolved by the platform arbiter, never their bookmaker
+        let is_house_backed = matches!(bet.market_type, MarketType::FixedOdds { .. });
+        let resolver = if is_house_backed {
+            ctx.accounts.platform_config.arbiter
+        } else {
+            bet.creator
+        };
+        require!(
+            resolver == *ctx.accounts.resolver.key,
+            BettingError::UnauthorizedResolver
+        );
+        require!(
+            clock.unix_timestamp >= bet.end_time,
+            BettingError::BettingStillOpen
+        );
+        require!(
+            bet.random_close_window == 0 || bet.effective_end_time != 0,
+            BettingError::RandomCloseNotFinalized
+        );
+        require!(
+            clock.unix_timestamp >= bet.end_time + bet.reveal_window,
+            BettingError::RevealWindowOpen
+        );
+        require!(
+            result_details.len() <= 300,
+            BettingError::InvalidResultDetails
+        );
+        require!(
+            bet.parent_market == Pubkey::default() || bet.parent_condition_met,
+            BettingError::ParentNotResolved
+        );
+
+        // Unrevealed stakes either go to the winners or wait to be refunded
+        if bet.forfeit_unrevealed {
+            bet.slashed_amount += bet.total_committed;
+            bet.total_committed = 0;
+        }
+
+        bet.is_resolved = true;
+        bet.winning_option = winning_option;
+        bet.resolved_value = resolved_value;
+        bet.resolution_weights_bps = weights_bps;
+        bet.resolved_at = clock.unix_timestamp;
+        bet.result_details = result_details;
+        apply_draw_fee_refund(bet);
+        if is_house_backed {
+            let house_vault = ctx
+                .accounts
+                .house_vault
+                .as_mut()
+                .ok_or(BettingError::HouseVaultRequired)?;
+            mark_house_reserve(bet, house_vault);
+        }
+
+        // Only the creator's own resolutions count towards their track record
+        if !is_house_backed {
+            let resolution_delay = clock.unix_timestamp - earliest_resolution_time(bet);
+            let creator_profile = &mut ctx.accounts.creator_profile;
+            creator_profile.markets_resolved += 1;
+            if resolution_delay <= ctx.accounts.platform_config.resolution_grace_period {
+                creator_profile.markets_resolved_on_time += 1;
+            }
+            creator_profile.total_resolution_delay += resolution_delay;
+            creator_profile.average_resolution_delay =
+                creator_profile.total_resolution_delay / creator_profile.markets_resolved as i64;
+        }
+
+        msg!(
+            "Bet resolved: option {} won, value {}",
+            winning_option,
+            resolved_value
+        );
+        Ok(())
+    }
+
+    /// Overturn a creator's resolution during the dispute window (only platform arbiter can do this)
+    pub fn overturn_resolution(
+        ctx: Context<OverturnResolution>,
+        _bet_id: String,
+        winning_option: u8,
+        result_details: String,
+        resolved_value: Option<i64>,
+        weights_bps: Vec<u16>,
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let platform_config = &ctx.accounts.platform_config;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(
+            platform_config.arbiter == *ctx.accounts.arbiter.key,
+            BettingError::UnauthorizedArbiter
+        );
+        require!(bet.is_resolved, BettingError::BetNotResolved);
+        require!(
+            clock.unix_timestamp < bet.resolved_at + platform_config.dispute_window,
+            BettingError::DisputeWindowClosed
+        );
+        let (winning_option, resolved_value) =
+            validate_resolution(bet, winning_option, resolved_value, &weights_bps)?;
+        require!(
+            winning_option != bet.winning_option
+                || resolved_value != bet.resolved_value
+                || weights_bps != bet.resolution_weights_bps,
+            BettingError::InvalidOption
+        );
+        require!(
+            result_details.len() <= 300,
+            BettingError::InvalidResultDetails
+        );
+
+        let overturned_option = bet.winning_option;
+        bet.winning_option = winning_option;
+        bet.resolved_value = resolved_value;
+        bet.resolution_weights_bps = weights_bps;
+        bet.result_details = result_details;
+        bet.is_overturned = true;
+        apply_draw_fee_refund(bet);
+        let is_house_backed = matches!(bet.market_type, MarketType::FixedOdds { .. });
+        if is_house_backed {
+            let house_vault = ctx
+                .accounts
+                .house_vault
+                .as_mut()
+                .ok_or(BettingError::HouseVaultRequired)?;
+            mark_house_reserve(bet, house_vault);
+        }
+
+        // The creator's bond goes to the corrected winners, unless the arbiter made the
+        // original call on a house-backed market
+        if !is_house_backed {
+            bet.slashed_amount += bet.creator_bond;
+            bet.creator_bond = 0;
+            ctx.accounts.creator_profile.markets_overturned += 1;
+        }
+
+        emit!(ResolutionOverturned {
+            bet_id: bet.bet_id.clone(),
+            overturned_option,
+            winning_option,
+            resolved_value,
+        });
+
+        msg!("Resolution overturned: option {} won", winning_option);
+        Ok(())
+    }
+
+    /// Pick the effective close of a random-close market (anyone can do this). The first call
+    /// commits to a future slot, a later call reads that slot's hash so no caller can pick it
+    pub fn finalize_random_close(ctx: Context<FinalizeRandomClose>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(bet.random_close_window > 0, BettingError::NotRandomClose);
+        require!(
+            bet.effective_end_time == 0,
+            BettingError::RandomCloseAlreadyFinalized
+        );
+        require!(
+            clock.unix_timestamp >= bet.end_time,
+            BettingError::BettingStillOpen
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(!bet.is_voided, BettingError::BetVoided);
+
+        // SlotHashes layout: entry count (u64), then (slot: u64, hash: [u8; 32]) newest first
+        let slot_hashes = ctx.accounts.slot_hashes.try_borrow_data()?;
+        let entry_count = u64::from_le_bytes(slot_hashes[..8].try_into().unwrap()) as usize;
+        let entries: Vec<(u64, u64)> = (0..entry_count)
+            .map(|index| {
+                let offset = 8 + index * 40;
+                let slot = u64::from_le_bytes(slot_hashes[offset..offset + 8].try_into().unwrap());
+                let hash =
+                    u64::from_le_bytes(slot_hashes[offset + 8..offset + 16].try_into().unwrap());
+                (slot, hash)
+            })
+            .collect();
+
+        // Commit to a seed slot, or to a new one if nobody read the last in time
+        // and its hash has dropped out of the sysvar
+        let seed_slot_lapsed = entries
+            .last()
+            .is_some_and(|&(oldest_slot, _)| oldest_slot > bet.random_close_seed_slot);
+        if bet.random_close_seed_slot == 0 || seed_slot_lapsed {
+            bet.random_close_seed_slot = clock.slot + RANDOM_CLOSE_SEED_DELAY;
+            msg!(
+                "Random close seed slot committed: {}",
+                bet.random_close_seed_slot
+            );
+            return Ok(());
+        }
+
+        // The seed is the hash of the first produced slot at or after the seed slot
+        let seed = entries
+            .iter()
+            .take_while(|&&(slot, _)| slot >= bet.random_close_seed_slot)
+            .last()
+            .map(|&(_, hash)| hash)
+            .ok_or(BettingError::RandomCloseSeedPending)?;
+        let close_slice = (seed % RANDOM_CLOSE_SLICES as u64) as usize;
+
+        // Bets placed in slices after the chosen one no longer count
+        for slice in close_slice + 1..RANDOM_CLOSE_SLICES {
+            bet.total_amount_a -= bet.late_amounts_a[slice];
+            bet.total_amount_b -= bet.late_amounts_b[slice];
+        }
+
+        let window_start = bet.end_time - bet.random_close_window;
+        bet.random_close_slice = close_slice as u8;
+        bet.effective_end_time = window_start
+            + bet.random_close_window * (close_slice as i64 + 1) / RANDOM_CLOSE_SLICES as i64;
+
+        emit!(RandomCloseFinalized {
+            bet_id: bet.bet_id.clone(),
+            effective_end_time: bet.effective_end_time,
+        });
+
+        msg!("Random close finalized at {}", bet.effective_end_time);
+        Ok(())
+    }
+
+    /// Push the betting deadline back (only creator can do this, only while open)
+    pub fn extend_end_time(
+        ctx: Context<UpdateBetWindow>,
+        _bet_id: String,
+        new_end_time: i64,
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let platform_config = &ctx.accounts.platform_config;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(
+            bet.creator == *ctx.accounts.creator.key,
+            BettingError::UnauthorizedResolver
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(
+            clock.unix_timestamp < bet.end_time,
+            BettingError::BettingClosed
+        );
+        require!(new_end_time > bet.end_time, BettingError::InvalidEndTime);
+        // Moving the deadline would shift a random-close window bettors already priced in
+        require!(
+            bet.random_close_window == 0,
+            BettingError::RandomCloseMarket
+        );
+        require!(
+            new_end_time - bet.original_end_time <= platform_config.max_end_time_extension,
+            BettingError::ExtensionTooLong
+        );
+
+        let old_end_time = bet.end_time;
+        bet.end_time = new_end_time;
+
+        emit!(EndTimeExtended {
+            bet_id: bet.bet_id.clone(),
+            old_end_time,
+            new_end_time,
+        });
+
+        msg!("Bet end time extended to {}", new_end_time);
+        Ok(())
+    }
+
+    /// Stop accepting bets immediately (only creator can do this, only while open)
+    pub fn close_betting_early(ctx: Context<UpdateBetWindow>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(
+            bet.creator == *ctx.accounts.creator.key,
+            BettingError::UnauthorizedResolver
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(
+            clock.unix_timestamp < bet.end_time,
+            BettingError::BettingClosed
+        );
+        require!(
+            bet.random_close_window == 0,
+            BettingError::RandomCloseMarket
+        );
+
+        let old_end_time = bet.end_time;
+        bet.end_time = clock.unix_timestamp;
+
+        emit!(BettingClosedEarly {
+            bet_id: bet.bet_id.clone(),
+            old_end_time,
+            closed_at: clock.unix_timestamp,
+        });
+
+        msg!("Betting closed early: {}", bet.bet_id);
+        Ok(())
+    }
+
+    /// Post the prices of a fixed-odds market (only its bookmaker can do this)
+    pub fn set_fixed_odds(
+        ctx: Context<SetFixedOdds>,
+        _bet_id: String,
+        odds_a_bps: u64, // decimal odds, 10000 = 1x, 0 = not offered
+        odds_b_bps: u64,
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+
+        // Validations
+        let MarketType::FixedOdds { bookmaker } = bet.market_type else {
+            return err!(BettingError::NotFixedOdds);
+        };
+        require!(
+            bookmaker == *ctx.accounts.bookmaker.key,
+            BettingError::UnauthorizedBookmaker
+        );
+        require!(
+            Clock::get()?.unix_timestamp < bet.end_time,
+            BettingError::BettingClosed
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(!bet.is_voided, BettingError::BetVoided);
+        require!(
+            (odds_a_bps == 0 || odds_a_bps > 10000) && (odds_b_bps == 0 || odds_b_bps > 10000),
+            BettingError::InvalidOdds
+        );
+        let max_odds_bps = ctx.accounts.platform_config.max_odds_bps;
+        require!(
+            odds_a_bps <= max_odds_bps && odds_b_bps <= max_odds_bps,
+            BettingError::OddsTooHigh
+        );
+
+        bet.fixed_odds_a_bps = odds_a_bps;
+        bet.fixed_odds_b_bps = odds_b_bps;
+
+        emit!(FixedOddsUpdated {
+            bet_id: bet.bet_id.clone(),
+            odds_a_bps,
+            odds_b_bps,
+        });
+
+        msg!("Fixed odds updated: {} / {}", odds_a_bps, odds_b_bps);
+        Ok(())
+    }
+
+    /// Claim maker fees (only bet creator can do this)
+    pub fn claim_maker_fees(ctx: Context<ClaimMakerFees>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+
+        // Validations
+        require!(
+            bet.is_resolved || bet.is_voided,
+            BettingError::BetNotResolved
+        );
+        require!(
+            bet.creator == *ctx.accounts.creator.key,
+            BettingError::UnauthorizedResolver
+        );
+        require!(bet.maker_fee_collected > 0, BettingError::NoFeesToClaim);
+
+        let fees_to_claim = bet.maker_fee_collected;
+
+        // Transfer fees to creator
+        **bet.to_account_info().try_borrow_mut_lamports()? -= fees_to_claim;
+        **ctx
+            .accounts
+            .creator
+            .to_account_info()
+            .try_borrow_mut_lamports()? += fees_to_claim;
+
+        bet.maker_fee_collected = 0;
+
+        msg!("Maker fees claimed: {} SOL", fees_to_claim);
+        Ok(())
+    }
+
+    /// Claim winnings for a winning bet
+    pub fn claim_winnings(ctx: Context<ClaimWinnings>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let user_bet = &mut ctx.accounts.user_bet;
+
+        // Validations
+        require!(bet.is_resolved, BettingError::BetNotResolved);
+        require!(
+            Clock::get()?.unix_timestamp
+                >= bet.resolved_at + ctx.accounts.platform_config.dispute_window,
+            BettingError::DisputeWindowOpen
+        );
+        require!(!user_bet.is_claimed, BettingError::AlreadyClaimed);
+        require!(
+            !is_excluded_by_random_close(bet, user_bet),
+            BettingError::LateBetExcluded
+        );
+        let is_draw = bet.winning_option == DRAW_OUTCOME;
+        let winnings = if is_draw {
+            // A draw or push hands every revealed position its stake back
+            require!(user_bet.option != 0, BettingError::NotWinner);
+            calculate_refund(user_bet.amount, total_pool(bet), bet.slashed_amount)
+        } else {
+            let payout_weight_bps = calculate_payout_weight_bps(bet, user_bet.option);
+            require!(payout_weight_bps > 0, BettingError::NotWinner);
+
+            let total_winning_pool = option_pool(bet, user_bet.option);
+
+            require!(total_winning_pool > 0, BettingError::NoWinnersFound);
+
+            let total_pool = total_pool(bet) + bet.slashed_amount;
+            let option_payout = (total_pool as u128 * payout_weight_bps as u128 / 10000) as u64;
+
+            // Fixed-odds winners get their locked payout plus a share of anything slashed,
+            // everyone else splits the pool by early-weighted stake
+            if let MarketType::FixedOdds { .. } = bet.market_type {
+                user_bet.potential_payout
+                    + calculate_winnings(user_bet.amount, total_winning_pool, bet.slashed_amount)?
+            } else {
+                calculate_winnings(
+                    user_bet.weighted_amount,
+                    weighted_option_pool(bet, user_bet.option),
+                    option_payout,
+                )?
+            }
+        };
+
+        // Transfer winnings to user
+        **bet.to_account_info().try_borrow_mut_lamports()? -= winnings;
+        **ctx
+            .accounts
+            .user
+            .to_account_info()
+            .try_borrow_mut_lamports()? += winnings;
+
+        user_bet.is_claimed = true;
+        user_bet.claimed_at = Clock::get()?.unix_timestamp;
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        if !is_draw {
+            user_profile.total_won += winnings;
+            user_profile.win_count += 1;
+        }
+        user_profile.open_positions = user_profile.open_positions.saturating_sub(1);
+        user_profile.open_exposure = user_profile
+            .open_exposure
+            .saturating_sub(user_bet.original_amount);
+
+        msg!("Winnings claimed: {} SOL", winnings);
+        Ok(())
+    }
+
+    /// Close a losing position to reclaim its rent
+    pub fn close_losing_bet(ctx: Context<CloseLosingBet>, _bet_id: String) -> Result<()> {
+        let bet = &ctx.accounts.bet;
+        let user_bet = &ctx.accounts.user_bet;
+
+        // Validations
+        require!(bet.is_resolved, BettingError::BetNotResolved);
+        require!(
+            Clock::get()?.unix_timestamp
+                >= bet.resolved_at + ctx.accounts.platform_config.dispute_window,
+            BettingError::DisputeWindowOpen
+        );
+        require!(
+            !is_excluded_by_random_close(bet, user_bet),
+            BettingError::LateBetExcluded
+        );
+        require!(
+            calculate_payout_weight_bps(bet, user_bet.option) == 0
+                && bet.winning_option != DRAW_OUTCOME,
+            BettingError::PositionNotLost
+        );
+        require!(
+            user_bet.option != 0 || bet.forfeit_unrevealed,
+            BettingError::PositionNotLost
+        );
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.open_positions = user_profile.open_positions.saturating_sub(1);
+        user_profile.open_exposure = user_profile
+            .open_exposure
+            .saturating_sub(user_bet.original_amount);
+
+        msg!("Losing bet closed: {}", bet.bet_id);
+        Ok(())
+    }
+
+    /// Return the creator's bond after a clean resolution (only creator can do this)
+    pub fn reclaim_creator_bond(ctx: Context<ReclaimCreatorBond>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+
+        // Validations
+        require!(
+            bet.creator == *ctx.accounts.creator.key,
+            BettingError::UnauthorizedResolver
+        );
+        // Markets voided for missing the deadline have already forfeited the bond
+        require!(
+            bet.is_resolved || bet.is_voided,
+            BettingError::BetNotResolved
+        );
+        require!(
+            Clock::get()?.unix_timestamp
+                >= bet.resolved_at + ctx.accounts.platform_config.dispute_window,
+            BettingError::DisputeWindowOpen
+        );
+        require!(bet.creator_bond > 0, BettingError::NoBondToReclaim);
+
+        let bond_to_reclaim = bet.creator_bond;
+
+        // Transfer bond to creator
+        **bet.to_account_info().try_borrow_mut_lamports()? -= bond_to_reclaim;
+        **ctx
+            .accounts
+            .creator
+            .to_account_info()
+            .try_borrow_mut_lamports()? += bond_to_reclaim;
+
+        bet.creator_bond = 0;
+
+        msg!("Creator bond reclaimed: {} SOL", bond_to_reclaim);
+        Ok(())
+    }
+
+    /// Void a market its creator failed to resolve in time (anyone can do this)
+    pub fn void_unresolved_bet(ctx: Context<VoidUnresolvedBet>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let platform_config = &ctx.accounts.platform_config;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(!bet.is_voided, BettingError::BetVoided);
+        // A conditional market's creator can't be late before its parent has settled
+        require!(
+            bet.parent_market == Pubkey::default() || bet.parent_condition_met,
+            BettingError::ParentNotResolved
+        );
+        require!(
+            platform_config.resolution_deadline > 0
+                && clock.unix_timestamp
+                    > earliest_resolution_time(bet) + platform_config.resolution_deadline,
+            BettingError::ResolutionDeadlineNotPassed
+        );
+
+        // The creator forfeits their bond and fees to the bettors, except on house-backed
+        // markets where resolving late was the arbiter's failure
+        bet.is_voided = true;
+        if !matches!(bet.market_type, MarketType::FixedOdds { .. }) {
+            bet.slashed_amount += bet.creator_bond + bet.maker_fee_collected;
+            bet.creator_bond = 0;
+            bet.maker_fee_collected = 0;
+            ctx.accounts.creator_profile.markets_voided += 1;
+        }
+
+        emit!(UnresolvedBetVoided {
+            bet_id: bet.bet_id.clone(),
+            slashed_amount: bet.slashed_amount,
+            voided_at: clock.unix_timestamp,
+        });
+
+        msg!("Bet voided: {}", bet.bet_id);
+        Ok(())
+    }
+
+    /// Apply a settled parent market to its conditional child (anyone can do this)
+    pub fn propagate_parent_resolution(
+        ctx: Context<PropagateParentResolution>,
+        _bet_id: String,
+    ) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let parent_market = &ctx.accounts.parent_market;
+        let clock = Clock::get()?;
+
+        // Validations
+        require!(
+            bet.parent_market != Pubkey::default(),
+            BettingError::NotConditional
+        );
+        require!(
+            !bet.parent_condition_met,
+            BettingError::ParentAlreadyPropagated
+        );
+        require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+        require!(!bet.is_voided, BettingError::BetVoided);
+        require!(
+            parent_market.is_voided
+                || (parent_market.is_resolved
+                    && clock.unix_timestamp
+                        >= parent_market.resolved_at + ctx.accounts.platform_config.dispute_window),
+            BettingError::ParentNotResolved
+        );
+
+        // The child only goes ahead if the parent settled on the linked outcome,
+        // otherwise every position is refunded
+        let condition_met =
+            !parent_market.is_voided && parent_market.winning_option == bet.parent_outcome;
+        if condition_met {
+            bet.parent_condition_met = true;
+            bet.parent_resolved_at = clock.unix_timestamp;
+        } else {
+            bet.is_voided = true;
+        }
+
+        emit!(ParentResolutionPropagated {
+            bet_id: bet.bet_id.clone(),
+            parent_market: bet.parent_market,
+            condition_met,
+        });
+
+        msg!(
+            "Parent resolution propagated: condition met {}",
+            condition_met
+        );
+        Ok(())
+    }
+
+    /// Refund a position in a voided market, or one excluded from payouts without losing
+    pub fn claim_refund(ctx: Context<ClaimRefund>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+        let user_bet = &mut ctx.accounts.user_bet;
+
+        // Validations
+        let refund = calculate_claimable_refund(bet, user_bet)?;
+        require!(!user_bet.is_claimed, BettingError::AlreadyClaimed);
+
+        // Transfer refund to user
+        **bet.to_account_info().try_borrow_mut_lamports()? -= refund;
+        **ctx
+            .accounts
+            .user
+            .to_account_info()
+            .try_borrow_mut_lamports()? += refund;
+
+        user_bet.is_claimed = true;
+        user_bet.claimed_at = Clock::get()?.unix_timestamp;
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.open_positions = user_profile.open_positions.saturating_sub(1);
+        user_profile.open_exposure = user_profile
+            .open_exposure
+            .saturating_sub(user_bet.original_amount);
+
+        msg!("Refund claimed: {} SOL", refund);
+        Ok(())
+    }
+
+    /// Allow or bar a market from being used as a parlay leg (only platform owner can do this)
+    pub fn set_parlay_eligible(
+        ctx: Context<SetParlayEligible>,
+        _bet_id: String,
+        eligible: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.platform_config.owner == *ctx.accounts.owner.key,
+            BettingError::UnauthorizedPlatformOwner
+        );
+
+        ctx.accounts.bet.parlay_eligible = eligible;
+
+        msg!("Parlay eligible: {}", eligible);
+        Ok(())
+    }
+
+    /// Place a parlay ticket over several markets, passed as remaining accounts in leg order
+    pub fn place_parlay(
+        ctx: Context<PlaceParlay>,
+        parlay_id: u64,
+        options: Vec<u8>, // option backed in each market
+        amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let markets = ctx.remaining_accounts;
+        let platform_config = &ctx.accounts.platform_config;
+
+        // Validations
+        require!(amount > 0, BettingError::InvalidAmount);
+        require!(
+            (2..=MAX_PARLAY_LEGS).contains(&options.len()) && markets.len() == options.len(),
+            BettingError::InvalidParlayLeg
+        );
+
+        // Price each leg at its posted or current parimutuel odds
+        let mut legs: Vec<ParlayLeg> = Vec::with_capacity(options.len());
+        let mut potential_payout = amount as u128;
+        for (market_info, &option) in markets.iter().zip(&options) {
+            let market = load_market(market_info)?;
+            require!(
+                !legs.iter().any(|leg| leg.market == market_info.key()),
+                BettingError::InvalidParlayLeg
+            );
+            require!(
+                clock.unix_timestamp >= market.start_time
+                    && clock.unix_timestamp < market.end_time
+                    && !market.is_resolved
+                    && !market.is_voided,
+                BettingError::BettingClosed
+            );
+            require!(
+                market.reveal_window == 0
+                    && !matches!(market.market_type, MarketType::Scalar { .. })
+                    && is_valid_option(&market, option),
+                BettingError::InvalidParlayLeg
+            );
+            require!(market.parlay_eligible, BettingError::ParlayLegNotEligible);
+
+            // Legs get the same gates as a single bet on the market, gated markets are
+            // left out since a ticket carries no per-market credentials
+            require!(
+                market.access_requirement == AccessRequirement::Open,
+                BettingError::ParlayLegNotEligible
+            );
+            require!(amount >= market.min_bet_amount, BettingError::BetTooLow);
+            require!(amount <= market.max_bet_amount, BettingError::BetTooHigh);
+            require!(
+                market.cutoff_mode != CutoffMode::BlockBets
+                    || market.cutoff_window == 0
+                    || clock.unix_timestamp < market.end_time - market.cutoff_window,
+                BettingError::CutoffWindowActive
+            );
+            require!(
+                random_close_slice(&market, clock.unix_timestamp).is_none(),
+                BettingError::RandomCloseWindowActive
+            );
+            require!(
+                market.max_position_per_user == 0 || amount <= market.max_position_per_user,
+                BettingError::PositionLimitExceeded
+            );
+
+            let odds_bps = match market.market_type {
+                MarketType::FixedOdds { .. } if option == 1 => market.fixed_odds_a_bps,
+                MarketType::FixedOdds { .. } => market.fixed_odds_b_bps,
+                _ => {
+                    let option_pool = option_pool(&market, option);
+                    require!(option_pool > 0, BettingError::LegNotPriceable);
+                    (total_pool(&market) as u128 * 10000 / option_pool as u128) as u64
+                }
+            };
+            require!(odds_bps > 0, BettingError::LegNotPriceable);
+            require!(
+                odds_bps <= platform_config.max_odds_bps,
+                BettingError::OddsTooHigh
+            );
+            potential_payout = potential_payout * odds_bps as u128 / 10000;
+
+            legs.push(ParlayLeg {
+                market: market_info.key(),
+                option,
+                odds_bps,
+            });
+        }
+        let potential_payout =
+            u64::try_from(potential_payout).map_err(|_| BettingError::ParlayPayoutTooHigh)?;
+
+        // The house must be able to cover the full payout once it holds the stake, and
+        // no single ticket may risk more than its share of the vault
+        require!(
+            vault_free_liquidity(&ctx.accounts.house_vault)? + amount >= potential_payout,
+            BettingError::InsufficientVaultLiquidity
+        );
+        require!(
+            potential_payout as u128
+                <= vault_net_asset_value(&ctx.accounts.house_vault)? as u128
+                    * platform_config.max_parlay_payout_bps as u128
+                    / 10000,
+            BettingError::ParlayPayoutTooHigh
+        );
+        let user_profile = &mut ctx.accounts.user_profile;
+        let max_open_exposure = platform_config.max_open_exposure_per_user;
+        require!(
+            max_open_exposure == 0 || user_profile.open_exposure + amount <= max_open_exposure,
+            BettingError::ExposureLimitExceeded
+        );
+
+        // Transfer stake from user to the house vault
+        let transfer_instruction = system_program::Transfer {
+            from: ctx.accounts.user.to_account_info(),
+            to: ctx.accounts.house_vault.to_account_info(),
+        };
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                transfer_instruction,
+            ),
+            amount,
+        )?;
+
+        let house_vault = &mut ctx.accounts.house_vault;
+        house_vault.total_liability += potential_payout;
+        house_vault.total_staked += amount;
+
+        // Update the user's profile, creating it on their first ticket
+        let user_profile = &mut ctx.accounts.user_profile;
+        if user_profile.user == Pubkey::default() {
+            user_profile.user = *ctx.accounts.user.key;
+            user_profile.created_at = clock.unix_timestamp;
+            user_profile.bump = ctx.bumps.user_profile;
+        }
+        user_profile.open_exposure += amount;
+        user_profile.open_positions += 1;
+
+        let parlay = &mut ctx.accounts.parlay;
+        parlay.user = *ctx.accounts.user.key;
+        parlay.parlay_id = parlay_id;
+        parlay.legs = legs;
+        parlay.stake = amount;
+        parlay.potential_payout = potential_payout;
+        parlay.placed_at = clock.unix_timestamp;
+        parlay.bump = ctx.bumps.parlay;
+
+        msg!(
+            "Parlay placed: {} SOL to win {} SOL",
+            amount,
+            potential_payout
+        );
+        Ok(())
+    }
+
+    /// Settle a parlay once its legs are final, passing its markets as remaining accounts
+    /// in leg order (anyone can do this)
+    pub fn settle_parlay(ctx: Context<SettleParlay>) -> Result<()> {
+        let parlay = &ctx.accounts.parlay;
+        let clock = Clock::get()?;
+        let dispute_window = ctx.accounts.platform_config.dispute_window;
+
+        // Validations
+        require!(
+            ctx.remaining_accounts.len() == parlay.legs.len(),
+            BettingError::InvalidParlayLeg
+        );
+
+        // Voided and drawn legs drop out of the price and a single lost leg loses the ticket
+        let mut payout = parlay.stake as u128;
+        let mut is_lost = false;
+        let mut is_pending = false;
+        for (market_info, leg) in ctx.remaining_accounts.iter().zip(&parlay.legs) {
+            require!(
+                market_info.key() == leg.market,
+                BettingError::InvalidParlayLeg
+            );
+            // A leg whose market was cancelled, or re-created since the ticket was placed,
+            // is void rather than leaving the ticket unsettleable
+            if *market_info.owner != crate::ID || market_info.data_is_empty() {
+                continue;
+            }
+            let market = load_market(market_info)?;
+            if market.is_voided || market.created_at > parlay.placed_at {
+                continue;
+            }
+            if !market.is_resolved || clock.unix_timestamp < market.resolved_at + dispute_window {
+                is_pending = true;
+            } else {
+                match apply_parlay_leg(payout, leg, &market) {
+                    Some(leg_payout) => payout = leg_payout,
+                    None => is_lost = true,
+                }
+            }
+        }
+        require!(is_lost || !is_pending, BettingError::ParlayLegsPending);
+        let payout = if is_lost { 0 } else { payout as u64 };
+
+        let house_vault = &mut ctx.accounts.house_vault;
+        house_vault.total_liability -= parlay.potential_payout;
+        house_vault.total_paid_out += payout;
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.open_positions = user_profile.open_positions.saturating_sub(1);
+        user_profile.open_exposure = user_profile.open_exposure.saturating_sub(parlay.stake);
+        if payout > 0 {
+            user_profile.total_won += payout;
+        }
+
+        // Transfer payout to user, the ticket's rent follows when it closes
+        if payout > 0 {
+            **house_vault.to_account_info().try_borrow_mut_lamports()? -= payout;
+            **ctx
+                .accounts
+                .user
+                .to_account_info()
+                .try_borrow_mut_lamports()? += payout;
+        }
+
+        emit!(ParlaySettled {
+            user: parlay.user,
+            parlay_id: parlay.parlay_id,
+            stake: parlay.stake,
+            payout,
+        });
+
+        msg!("Parlay settled: {} SOL paid", payout);
+        Ok(())
+    }
+
+    /// Return a settled fixed-odds market's surplus to the house vault (anyone can do this)
+    pub fn settle_house_reserve(ctx: Context<SettleHouseReserve>, _bet_id: String) -> Result<()> {
+        let bet = &mut ctx.accounts.bet;
+
+        // Validations
+        require!(
+            matches!(bet.market_type, MarketType::FixedOdds { .. }),
+            BettingError::NotFixedOdds
+        );
+        require!(!bet.house_settled, BettingError::HouseReserveSettled);
+        require!(
+            bet.is_voided
+                || (bet.is_resolved
+                    && Clock::get()?.unix_timestamp
+                        >= bet.resolved_at + ctx.accounts.platform_config.dispute_window),
+            BettingError::BetNotResolved
+        );
+
+        // Keep what bettors are owed, the house takes the rest of the stakes and its reserve
+        let surplus = house_surplus(bet);
+
+        let house_vault = &mut ctx.accounts.house_vault;
+        **bet.to_account_info().try_borrow_mut_lamports()? -= surplus;
+        **house_vault.to_account_info().try_borrow_mut_lamports()? += surplus;
+
+        house_vault.total_market_reserves -= bet.house_reserve_mark;
+        bet.house_reserve_mark = 0;
+        bet.house_settled = true;
+
+        msg!("House reserve settled: {} SOL returned", surplus);
+        Ok(())
+    }
+
+    /// Cancel bet (only creator can do this, only if no bets placed)
+    pub fn cancel_bet(ctx: Context<CancelBet>, _bet_id: String) -> Result<()> {
+        let bet = &ctx.accounts.bet;
+
+        // Validations
+        require!(
+            bet.creator == *ctx.accounts.creator.key,
+            BettingError::UnauthorizedResolver
+        );
+        validate_cancellation(bet)?;
+
+        ctx.accounts.creator_profile.markets_cancelled += 1;
+
+        msg!("Bet cancelled: {}", bet.bet_id);
+        Ok(())
+    }
+
+    /// Get bet statistics
+    pub fn get_bet_stats(ctx: Context<GetBetStats>, _bet_id: String) -> Result<BetStats> {
+        let bet = &ctx.accounts.bet;
+        let clock = Clock::get()?;
+
+        let total_pool = total_pool(bet);
+        let time_remaining = if bet.end_time > clock.unix_timestamp {
+            bet.end_time - clock.unix_timestamp
+        } else {
+            0
+        };
+        let time_until_open = if bet.start_time > clock.unix_timestamp {
+            bet.start_time - clock.unix_timestamp
+        } else {
+            0
+        };
+
+        let (odds_a, odds_b) = calculate_odds(bet.total_amount_a, bet.total_amount_b);
+        let bucket_probabilities = calculate_bucket_probabilities(&bet.bucket_amounts);
+
+        Ok(BetStats {
+            total_pool,
+            odds_a,
+            odds_b,
+            bucket_probabilities,
+            total_bettors: bet.total_bettors,
+            time_remaining,
+            time_until_open,
+        })
+    }
+
+    /// Quote the fee a user would pay to bet `amount` on this market
+    pub fn preview_fee(
+        ctx: Context<PreviewFee>,
+        _bet_id: String,
+        option: u8,
+        amount: u64,
+    ) -> Result<FeeQuote> {
+        let bet = &ctx.accounts.bet;
+        let now = Clock::get()?.unix_timestamp;
+        let lifetime_volume = ctx
+            .accounts
+            .user_profile
+            .as_ref()
+            .map_or(0, |user_profile| user_profile.total_wagered);
+
+        let (fee_bps, curve_fee_bps) = calculate_fee_bps(
+            bet,
+            &ctx.accounts.platform_config.fee_tiers,
+            lifetime_volume,
+            option,
+            amount,
+            now,
+        );
+        let fee = calculate_maker_fee(amount, fee_bps);
+
+        Ok(FeeQuote {
+            fee_bps,
+            curve_fee_bps,
+            fee,
+            net_amount: amount - fee,
+            lifetime_volume,
+        })
+    }
+}
+
+// Helper functions
+fn verify_bettor_access(accounts: &PlaceBet, allowlist_proof: &[[u8; 32]], now: i64) -> Result<()> {
+    let user = accounts.user.key();
+
+    match accounts.bet.access_requirement {
+        AccessRequirement::Open => {}
+        AccessRequirement::Attestation { issuer } => {
+            let attestation = accounts
+                .attestation
+                .as_ref()
+                .ok_or(BettingError::AttestationRequired)?;
+            require!(
+                attestation.issuer == issuer && attestation.subject == user,
+                BettingError::AttestationRequired
+            );
+            require!(
+                !attestation.is_revoked
+                    && (attestation.expires_at == 0 || now < attestation.expires_at),
+                BettingError::AttestationInvalid
+            );
+        }
+        AccessRequirement::TokenHolder { mint, min_amount } => {
+            let token_account = accounts
+                .access_token_account
+                .as_ref()
+                .ok_or(BettingError::TokenOwnershipRequired)?;
+            require!(
+                token_account.owner == user
+                    && token_account.mint == mint
+                    && token_account.amount >= min_amount.max(1),
+                BettingError::TokenOwnershipRequired
+            );
+        }
+        AccessRequirement::NftCollection { collection } => {
+            let token_account = accounts
+                .access_token_account
+                .as_ref()
+                .ok_or(BettingError::CollectionMembershipRequired)?;
+            let metadata = accounts
+                .access_metadata
+                .as_ref()
+                .ok_or(BettingError::CollectionMembershipRequired)?;
+            require!(
+                token_account.owner == user
+                    && token_account.amount > 0
+                    && metadata.mint == token_account.mint,
+                BettingError::CollectionMembershipRequired
+            );
+            require!(
+                metadata
+                    .collection
+                    .as_ref()
+                    .is_some_and(|item| item.verified && item.key == collection),
+                BettingError::CollectionMembershipRequired
+            );
+        }
+        AccessRequirement::MerkleAllowlist { root } => {
+            require!(
+                verify_merkle_proof(hashv(&[user.as_ref()]).to_bytes(), allowlist_proof, root),
+                BettingError::NotOnAllowlist
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Walks a sorted-pair SHA-256 Merkle proof from `leaf` up to `root`
+fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
+
+fn validate_category_settings(
+    platform_config: &PlatformConfig,
+    name: &str,
+    default_maker_fee_bps: u16,
+    default_min_bet_amount: u64,
+    default_max_bet_amount: u64,
+) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_CATEGORY_NAME_LENGTH,
+        BettingError::InvalidCategory
+    );
+    require!(
+        default_maker_fee_bps as u64 <= MAX_FEE_RATE
+            && default_maker_fee_bps <= platform_config.max_maker_fee_bps,
+        BettingError::FeeTooHigh
+    );
+    require!(default_min_bet_amount > 0, BettingError::InvalidAmount);
+    require!(
+        default_max_bet_amount >= default_min_bet_amount,
+        BettingError::InvalidAmount
+    );
+    Ok(())
+}
+
+fn calculate_maker_fee(amount: u64, maker_fee_bps: u16) -> u64 {
+    (amount * maker_fee_bps as u64) / 10000
+}
+
+/// Applies the discount of the highest tier the user's lifetime volume qualifies for
+fn calculate_tiered_fee_bps(
+    maker_fee_bps: u16,
+    fee_tiers: &[FeeTier],
+    lifetime_volume: u64,
+) -> u16 {
+    let discount_bps = fee_tiers
+        .iter()
+        .rev()
+        .find(|tier| lifetime_volume >= tier.min_volume)
+        .map_or(0, |tier| tier.discount_bps);
+
+    ((maker_fee_bps as u64 * (10000 - discount_bps as u64)) / 10000) as u16
+}
+
+/// Extra fee for bets inside a fee-ramp cutoff window, rising linearly to the maximum at end time
+fn calculate_cutoff_fee_bps(bet: &BetState, now: i64) -> u16 {
+    let CutoffMode::FeeRamp { max_extra_fee_bps } = bet.cutoff_mode else {
+        return 0;
+    };
+    let cutoff_start = bet.end_time - bet.cutoff_window;
+    if bet.cutoff_window == 0 || now < cutoff_start {
+        return 0;
+    }
+
+    ((max_extra_fee_bps as i64 * (now - cutoff_start)) / bet.cutoff_window) as u16
+}
+
+/// Total fee for betting `amount` on `option` at `now`, and the part of it from the
+/// fee curve. The tiered, cutoff and curve fees together never exceed the max fee rate
+fn calculate_fee_bps(
+    bet: &BetState,
+    fee_tiers: &[FeeTier],
+    lifetime_volume: u64,
+    option: u8,
+    amount: u64,
+    now: i64,
+) -> (u16, u16) {
+    let base_fee_bps = calculate_tiered_fee_bps(bet.maker_fee_bps, fee_tiers, lifetime_volume)
+        + calculate_cutoff_fee_bps(bet, now);
+    // Pools hold net stakes, so the curve sees the bet net of the base fee
+    let base_net_amount = amount - calculate_maker_fee(amount, base_fee_bps);
+    let curve_fee_bps = calculate_curve_fee_bps(bet, option, base_net_amount, now);
+
+    let fee_bps = (base_fee_bps + curve_fee_bps).min(MAX_FEE_RATE as u16);
+    (fee_bps, fee_bps.saturating_sub(base_fee_bps))
+}
+
+/// Extra fee from the market's fee curve for adding `net_amount` to `option` at `now`
+fn calculate_curve_fee_bps(bet: &BetState, option: u8, net_amount: u64, now: i64) -> u16 {
+    match bet.fee_curve {
+        FeeCurve::Flat => 0,
+        FeeCurve::TimeToClose { max_extra_fee_bps } => {
+            let duration = (bet.end_time - bet.start_time).max(1);
+            let elapsed = (now - bet.start_time).clamp(0, duration);
+            (max_extra_fee_bps as i64 * elapsed / duration) as u16
+        }
+        FeeCurve::Imbalance { max_extra_fee_bps } => {
+            // Only bets that leave their side the larger one pay more, scaled by the gap
+            let (bet_side, other_side) = if option == 1 {
+                (bet.total_amount_a, bet.total_amount_b)
+            } else {
+                (bet.total_amount_b, bet.total_amount_a)
+            };
+            let bet_side = bet_side + net_amount;
+            if bet_side <= other_side {
+                return 0;
+            }
+
+            (max_extra_fee_bps as u128 * (bet_side - other_side) as u128
+                / (bet_side + other_side) as u128) as u16
+        }
+    }
+}
+
+/// Which slice of the random-close window `now` falls in, if any
+fn random_close_slice(bet: &BetState, now: i64) -> Option<usize> {
+    let window_start = bet.end_time - bet.random_close_window;
+    if bet.random_close_window == 0 || now < window_start {
+        return None;
+    }
+
+    let slice = (now - window_start) * RANDOM_CLOSE_SLICES as i64 / bet.random_close_window;
+    Some((slice as usize).min(RANDOM_CLOSE_SLICES - 1))
+}
+
+fn is_excluded_by_random_close(bet: &BetState, user_bet: &UserBetState) -> bool {
+    bet.effective_end_time != 0
+        && user_bet.late_slice != 0
+        && user_bet.late_slice - 1 > bet.random_close_slice
+}
+
+/// Checks a market can still be closed by its creator without stranding any stake
+fn validate_cancellation(bet: &BetState) -> Result<()> {
+    // Counts hidden commit-reveal stakes too, which aren't in any pool yet
+    require!(bet.total_bettors == 0, BettingError::BetsAlreadyPlaced);
+    require!(!bet.is_resolved, BettingError::BetAlreadyResolved);
+    require!(bet.child_count == 0, BettingError::MarketHasChildren);
+    // Parlay legs priced from posted odds need no stake in the market itself
+    require!(!bet.parlay_eligible, BettingError::MarketParlayEligible);
+    Ok(())
+}
+
+/// Lamports a refundable position gets back from a voided market, a random close or
+/// an unrevealed commitment
+fn calculate_claimable_refund(bet: &BetState, user_bet: &UserBetState) -> Result<u64> {
+    let is_late_bet = is_excluded_by_random_close(bet, user_bet);
+    let is_unrevealed = bet.reveal_window > 0 && user_bet.option == 0;
+    require!(
+        bet.is_voided
+            || is_late_bet
+            || (is_unrevealed && bet.is_resolved && !bet.forfeit_unrevealed),
+        BettingError::NotRefundable
+    );
+
+    // Bets excluded by a random close or never revealed get their net stake back,
+    // fees are not refunded
+    Ok(if is_late_bet || is_unrevealed {
+        user_bet.amount
+    } else {
+        calculate_refund(user_bet.amount, total_pool(bet), bet.slashed_amount)
+    })
+}
+
+/// Earliest moment the creator is able to resolve the market, after any reveal window
+/// and the parent market's result
+fn earliest_resolution_time(bet: &BetState) -> i64 {
+    (bet.end_time + bet.reveal_window).max(bet.parent_resolved_at)
+}
+
+/// Checks a resolution against the market type, returning the option and value to store
+fn validate_resolution(
+    bet: &BetState,
+    winning_option: u8,
+    resolved_value: Option<i64>,
+    weights_bps: &[u16],
+) -> Result<(u8, i64)> {
+    // A split resolution pays every option by weight instead of naming a winner
+    if !weights_bps.is_empty() {
+        require!(
+            matches!(
+                bet.market_type,
+                MarketType::Binary | MarketType::Line { .. } | MarketType::Bucketed { .. }
+            ) && weights_bps.len() == option_count(bet)
+                && weights_bps.iter().map(|&weight| weight as u64).sum::<u64>() == 10000,
+            BettingError::InvalidResolutionWeights
+        );
+        return Ok((0, resolved_value.unwrap_or_default()));
+    }
+
+    match bet.market_type {
+        MarketType::Binary | MarketType::FixedOdds { .. } => {
+            require!(
+                winning_option == 1 || winning_option == 2 || winning_option == DRAW_OUTCOME,
+                BettingError::InvalidOption
+            );
+            Ok((winning_option, 0))
+        }
+        MarketType::Line { line } => {
+            // Landing exactly on the line is a push
+            let resolved_value = resolved_value.ok_or(BettingError::ResolvedValueRequired)?;
+            let winning_option = match resolved_value.cmp(&line) {
+                std::cmp::Ordering::Greater => 1,
+                std::cmp::Ordering::Less => 2,
+                std::cmp::Ordering::Equal => DRAW_OUTCOME,
+            };
+            Ok((winning_option, resolved_value))
+        }
+        MarketType::Scalar { .. } => {
+            let resolved_value = resolved_value.ok_or(BettingError::ResolvedValueRequired)?;
+            Ok((0, resolved_value))
+        }
+        MarketType::Bucketed { edge } => {
+            let resolved_value = resolved_value.ok_or(BettingError::ResolvedValueRequired)?;
+            Ok((winning_bucket(bet, resolved_value, edge), resolved_value))
+        }
+    }
+}
+
+/// Hands the creator's fees back to bettors when a market that refunds them draws
+fn apply_draw_fee_refund(bet: &mut BetState) {
+    if bet.winning_option == DRAW_OUTCOME && bet.refund_fees_on_draw {
+        bet.slashed_amount += bet.maker_fee_collected;
+        bet.maker_fee_collected = 0;
+    }
+}
+
+/// Bucket (numbered from 1) paid out for `value`, falling back to the nearest bucket
+/// with stakes when the containing one is empty
+fn winning_bucket(bet: &BetState, value: i64, edge: BucketEdge) -> u8 {
+    // Values outside the range land in the first or last bucket
+    let inner_edges = &bet.bucket_bounds[1..bet.bucket_bounds.len() - 1];
+    let containing = inner_edges
+        .iter()
+        .filter(|&&bound| match edge {
+            BucketEdge::LowerInclusive => bound <= value,
+            BucketEdge::UpperInclusive => bound < value,
+        })
+        .count();
+
+    let bucket_count = bet.bucket_amounts.len();
+    let nearest = (0..bucket_count)
+        .flat_map(|distance| {
+            [
+                containing.checked_sub(distance),
+                Some(containing + distance),
+            ]
+        })
+        .flatten()
+        .find(|&bucket| bucket < bucket_count && bet.bucket_amounts[bucket] > 0)
+        .unwrap_or(containing);
+    nearest as u8 + 1
+}
+
+fn option_count(bet: &BetState) -> usize {
+    match bet.market_type {
+        MarketType::Bucketed { .. } => bet.bucket_amounts.len(),
+        _ => 2,
+    }
+}
+
+fn is_valid_option(bet: &BetState, option: u8) -> bool {
+    (1..=option_count(bet)).contains(&(option as usize))
+}
+
+/// Net stake backing `option`, where option 0 holds unrevealed commitments
+fn option_pool(bet: &BetState, option: u8) -> u64 {
+    match (&bet.market_type, option) {
+        (_, 0) => bet.total_committed,
+        (MarketType::Bucketed { .. }, _) => bet.bucket_amounts[option as usize - 1],
+        (_, 1) => bet.total_amount_a,
+        _ => bet.total_amount_b,
+    }
+}
+
+fn option_pool_mut(bet: &mut BetState, option: u8) -> &mut u64 {
+    match (&bet.market_type, option) {
+        (_, 0) => &mut bet.total_committed,
+        (MarketType::Bucketed { .. }, _) => &mut bet.bucket_amounts[option as usize - 1],
+        (_, 1) => &mut bet.total_amount_a,
+        _ => &mut bet.total_amount_b,
+    }
+}
+
+/// Reads a market passed through remaining accounts
+fn load_market(market_info: &AccountInfo) -> Result<BetState> {
+    require!(
+        *market_info.owner == crate::ID,
+        BettingError::InvalidParlayLeg
+    );
+    let data = market_info.try_borrow_data()?;
+    BetState::try_deserialize(&mut &data[..])
+}
+
+/// Ticket payout after a final leg, or `None` if the leg lost. A drawn leg drops out of
+/// the price and a split resolution pays the leg its option's share of it
+fn apply_parlay_leg(payout: u128, leg: &ParlayLeg, market: &BetState) -> Option<u128> {
+    if market.winning_option == DRAW_OUTCOME {
+        return Some(payout);
+    }
+
+    let payout_weight_bps = calculate_payout_weight_bps(market, leg.option);
+    if payout_weight_bps == 0 {
+        return None;
+    }
+    Some(payout * leg.odds_bps as u128 * payout_weight_bps as u128 / 100_000_000)
+}
+
+/// Lamports in the house vault not needed for rent, open parlay payouts or pending deposits
+fn vault_free_liquidity(house_vault: &Account<HouseVault>) -> Result<u64> {
+    let vault_info = house_vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+    Ok(vault_info.lamports().saturating_sub(
+        rent_exempt + house_vault.total_liability + house_vault.total_pending_deposits,
+    ))
+}
+
+/// What a resolved or voided fixed-odds market returns to the house vault: its stakes
+/// and reserve less what bettors are owed
+fn house_surplus(bet: &BetState) -> u64 {
+    let owed = if bet.is_voided || bet.winning_option == DRAW_OUTCOME {
+        total_pool(bet)
+    } else if bet.winning_option == 1 {
+        bet.liability_a
+    } else {
+        bet.liability_b
+    };
+    total_pool(bet) + bet.house_reserve - owed
+}
+
+/// Revalue a resolved fixed-odds market in the house vault at what it will return,
+/// so LPs don't trade at the face value of a reserve already lost or won
+fn mark_house_reserve(bet: &mut BetState, house_vault: &mut HouseVault) {
+    let surplus = house_surplus(bet);
+    house_vault.total_market_reserves =
+        house_vault.total_market_reserves - bet.house_reserve_mark + surplus;
+    bet.house_reserve_mark = surplus;
+}
+
+/// Value of the house vault to its LPs: its capital, including reserves lent to
+/// fixed-odds markets at their current mark, less the payouts owed on open parlays
+/// and deposits still waiting for their shares
+fn vault_net_asset_value(house_vault: &Account<HouseVault>) -> Result<u64> {
+    let vault_info = house_vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+    Ok(
+        (vault_info.lamports().saturating_sub(rent_exempt) + house_vault.total_market_reserves)
+            .saturating_sub(house_vault.total_liability + house_vault.total_pending_deposits),
+    )
+}
+
+/// Shares minted for a deposit, priced against virtual shares and assets so the
+/// vault can't be pre-loaded to round a victim's deposit down to nothing
+fn calculate_lp_shares(amount: u64, share_supply: u64, net_asset_value: u64) -> u64 {
+    (amount as u128 * (share_supply as u128 + LP_VIRTUAL_SHARES as u128)
+        / (net_asset_value as u128 + LP_VIRTUAL_ASSETS as u128)) as u64
+}
+
+/// Lamports paid for burning LP shares, using the same virtual offset as deposits
+/// and never more than the vault is worth
+fn calculate_lp_redemption(shares: u64, share_supply: u64, net_asset_value: u64) -> u64 {
+    let amount = shares as u128 * (net_asset_value as u128 + LP_VIRTUAL_ASSETS as u128)
+        / (share_supply as u128 + LP_VIRTUAL_SHARES as u128);
+    amount.min(net_asset_value as u128) as u64
+}
+
+/// Stake backing `option` when splitting its payout, after any early-bettor bonus
+fn weighted_option_pool(bet: &BetState, option: u8) -> u64 {
+    if bet.weighted_pools.is_empty() {
+        option_pool(bet, option)
+    } else {
+        bet.weighted_pools[option as usize - 1]
+    }
+}
+
+/// Scales a net stake by the market's early-bettor bonus, which decays to nothing
+/// by the end time
+fn calculate_early_weighted_amount(bet: &BetState, net_amount: u64, now: i64) -> u64 {
+    let duration = (bet.end_time - bet.start_time).max(1);
+    let elapsed = (now - bet.start_time).clamp(0, duration);
+    let bonus_bps = match bet.early_bonus {
+        EarlyBonus::None => 0,
+        EarlyBonus::Linear { max_bonus_bps } => {
+            max_bonus_bps as u64 * (duration - elapsed) as u64 / duration as u64
+        }
+        EarlyBonus::Exponential {
+            max_bonus_bps,
+            half_life,
+        } => {
+            // Halve the bonus every half-life, interpolating within each one
+            let halvings = (elapsed / half_life).min(16) as u32;
+            let current = max_bonus_bps as u64 >> halvings;
+            let next = current >> 1;
+            current - (current - next) * (elapsed % half_life) as u64 / half_life as u64
+        }
+    };
+
+    (net_amount as u128 * (10000 + bonus_bps) as u128 / 10000) as u64
+}
+
+/// Revealed net stakes across every option
+fn total_pool(bet: &BetState) -> u64 {
+    bet.total_amount_a + bet.total_amount_b + bet.bucket_amounts.iter().sum::<u64>()
+}
+
+/// Share of the payout pool, in basis points, owed to everyone who backed `option`
+fn calculate_payout_weight_bps(bet: &BetState, option: u8) -> u64 {
+    match bet.market_type {
+        MarketType::Binary
+        | MarketType::Line { .. }
+        | MarketType::Bucketed { .. }
+        | MarketType::FixedOdds { .. } => {
+            if !bet.resolution_weights_bps.is_empty() {
+                calculate_split_weight_bps(bet, option)
+            } else if option == bet.winning_option {
+                10000
+            } else {
+                0
+            }
+        }
+        MarketType::Scalar {
+            lower_bound,
+            upper_bound,
+        } => {
+            // Longs (option A) take the share of the range below the resolved value
+            let long_bps = if bet.total_amount_a == 0 {
+                0
+            } else if bet.total_amount_b == 0 {
+                10000
+            } else {
+                // Widen before subtracting so bounds spanning most of i64 can't overflow
+                let resolved_value = bet.resolved_value.clamp(lower_bound, upper_bound) as i128;
+                ((resolved_value - lower_bound as i128) * 10000
+                    / (upper_bound as i128 - lower_bound as i128)) as u64
+            };
+            match option {
+                1 => long_bps,
+                2 => 10000 - long_bps,
+                _ => 0,
+            }
+        }
+    }
+}
+
+/// An option's split-resolution weight, rescaled over the options anyone backed
+/// so no share of the pool is left unclaimable
+fn calculate_split_weight_bps(bet: &BetState, option: u8) -> u64 {
+    if option == 0 || option_pool(bet, option) == 0 {
+        return 0;
+    }
+
+    let staked_weight: u64 = (1..=option_count(bet) as u8)
+        .filter(|&staked_option| option_pool(bet, staked_option) > 0)
+        .map(|staked_option| bet.resolution_weights_bps[staked_option as usize - 1] as u64)
+        .sum();
+    if staked_weight == 0 {
+        return 0;
+    }
+
+    bet.resolution_weights_bps[option as usize - 1] as u64 * 10000 / staked_weight
+}
+
+fn calculate_referral_reward(maker_fee: u64, referral_fee_bps: u16) -> u64 {
+    (maker_fee * referral_fee_bps as u64) / 10000
+}
+
+fn calculate_winnings(user_amount: u64, total_winning_pool: u64, total_pool: u64) -> Result<u64> {
+    require!(total_winning_pool > 0, BettingError::NoWinnersFound);
+
+    let winnings = (user_amount as u128 * total_pool as u128) / total_winning_pool as u128;
+    Ok(winnings as u64)
+}
+
+/// Returns the net stake plus a pro-rata share of any slashed bond and fees
+fn calculate_refund(user_amount: u64, total_pool: u64, slashed_amount: u64) -> u64 {
+    if total_pool == 0 {
+        return user_amount;
+    }
+
+    let slashed_share = (user_amount as u128 * slashed_amount as u128) / total_pool as u128;
+    user_amount + slashed_share as u64
+}
+
+fn calculate_odds(amount_a: u64, amount_b: u64) -> (u64, u64) {
+    let total = amount_a + amount_b;
+    if total == 0 {
+        return (5000, 5000); // 50/50 odds in basis points
+    }
+
+    let odds_a = (amount_b * 10000) / total;
     let odds_b = (amount_a * 10000) / total;
     (odds_a, odds_b)
 }
 
-// Account validation structs
+/// Each bucket's share of the pool in basis points, split evenly while nothing is staked
+fn calculate_bucket_probabilities(bucket_amounts: &[u64]) -> Vec<u64> {
+    let total: u64 = bucket_amounts.iter().sum();
+    bucket_amounts
+        .iter()
+        .map(|&amount| {
+            if total == 0 {
+                10000 / bucket_amounts.len() as u64
+            } else {
+                (amount as u128 * 10000 / total as u128) as u64
+            }
+        })
+        .collect()
+}
+
+// Account validation structs
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(
+        init,
+        seeds = [b"platform_config"],
+        bump,
+        space = 8 + PlatformConfig::INIT_SPACE,
+        payer = owner,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct ApproveCreator<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_approval", creator.as_ref()],
+        bump,
+        space = 8 + CreatorApproval::INIT_SPACE,
+        payer = owner,
+    )]
+    pub creator_approval: Account<'info, CreatorApproval>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureLiquidityVault<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"house_vault"],
+        bump,
+        space = 8 + HouseVault::INIT_SPACE,
+        payer = owner,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        init_if_needed,
+        seeds = [b"lp_mint"],
+        bump,
+        mint::decimals = 9,
+        mint::authority = house_vault,
+        mint::token_program = token_program,
+        payer = owner,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        address = house_vault.lp_mint,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider,
+        token::token_program = token_program,
+    )]
+    pub provider_lp_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [b"lp_deposit", provider.key().as_ref()],
+        bump,
+        space = 8 + DepositRequest::INIT_SPACE,
+        payer = provider,
+    )]
+    pub deposit_request: Account<'info, DepositRequest>,
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDepositShares<'info> {
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        address = house_vault.lp_mint,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"lp_deposit", deposit_request.provider.as_ref()],
+        bump = deposit_request.bump,
+        close = provider,
+    )]
+    pub deposit_request: Account<'info, DepositRequest>,
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider,
+        token::token_program = token_program,
+    )]
+    pub provider_lp_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: only receives the request's rent and owns the share account, pinned to the depositor
+    #[account(mut, address = deposit_request.provider)]
+    pub provider: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(address = house_vault.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = provider,
+        token::token_program = token_program,
+    )]
+    pub provider_lp_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [b"lp_escrow", provider.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = house_vault,
+        token::token_program = token_program,
+        payer = provider,
+    )]
+    pub lp_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [b"lp_withdrawal", provider.key().as_ref()],
+        bump,
+        space = 8 + WithdrawalRequest::INIT_SPACE,
+        payer = provider,
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        address = house_vault.lp_mint,
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"lp_escrow", provider.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = house_vault,
+        token::token_program = token_program,
+    )]
+    pub lp_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"lp_withdrawal", provider.key().as_ref()],
+        bump = withdrawal_request.bump,
+        close = provider,
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeCreatorApproval<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_approval", creator_approval.creator.as_ref()],
+        bump = creator_approval.bump,
+    )]
+    pub creator_approval: Account<'info, CreatorApproval>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(category_id: u32)]
+pub struct CreateCategory<'info> {
+    #[account(
+        init,
+        seeds = [b"category", category_id.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Category::INIT_SPACE,
+        payer = owner,
+    )]
+    pub category: Account<'info, Category>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCategory<'info> {
+    #[account(
+        mut,
+        seeds = [b"category", category.id.to_le_bytes().as_ref()],
+        bump = category.bump,
+    )]
+    pub category: Account<'info, Category>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct CreateBet<'info> {
+    #[account(
+        init,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+        space = 8 + BetState::INIT_SPACE,
+        payer = creator,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        mut,
+        seeds = [b"category", category.id.to_le_bytes().as_ref()],
+        bump = category.bump,
+    )]
+    pub category: Account<'info, Category>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        init_if_needed,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump,
+        space = 8 + CreatorProfile::INIT_SPACE,
+        payer = creator,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    #[account(
+        seeds = [b"creator_approval", creator.key().as_ref()],
+        bump = creator_approval.bump,
+    )]
+    pub creator_approval: Option<Account<'info, CreatorApproval>>,
+    #[account(mut)]
+    pub parent_market: Option<Account<'info, BetState>>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct PlaceBet<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        init_if_needed,
+        seeds = [b"user_bet", bet_id.as_bytes(), user.key().as_ref()],
+        bump,
+        space = 8 + UserBetState::INIT_SPACE,
+        payer = user,
+    )]
+    pub user_bet: Account<'info, UserBetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        init_if_needed,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump,
+        space = 8 + UserProfile::INIT_SPACE,
+        payer = user,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [b"creator_profile", bet.creator.as_ref()],
+        bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    #[account(
+        mut,
+        address = bet.category,
+    )]
+    pub category: Account<'info, Category>,
+    #[account(
+        mut,
+        seeds = [b"referral", referral.referrer.as_ref()],
+        bump = referral.bump,
+    )]
+    pub referral: Option<Account<'info, ReferralState>>,
+    #[account(
+        seeds = [b"attestation", attestation.issuer.as_ref(), user.key().as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+    pub access_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub access_metadata: Option<Account<'info, MetadataAccount>>,
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Option<Account<'info, HouseVault>>, // fixed-odds markets only
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(subject: Pubkey)]
+pub struct IssueAttestation<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"attestation", issuer.key().as_ref(), subject.as_ref()],
+        bump,
+        space = 8 + Attestation::INIT_SPACE,
+        payer = issuer,
+    )]
+    pub attestation: Account<'info, Attestation>,
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"attestation", issuer.key().as_ref(), attestation.subject.as_ref()],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+    pub issuer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct RevealBet<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        mut,
+        seeds = [b"user_bet", bet_id.as_bytes(), user.key().as_ref()],
+        bump,
+    )]
+    pub user_bet: Account<'info, UserBetState>,
+    pub user: Signer<'info>,
+}
+
 #[derive(Accounts)]
-#[instruction(bet_id: String)]
-pub struct CreateBet<'info> {
+pub struct RegisterReferrer<'info> {
     #[account(
         init,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump,
+        space = 8 + ReferralState::INIT_SPACE,
+        payer = referrer,
+    )]
+    pub referral: Account<'info, ReferralState>,
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump,
+    )]
+    pub referral: Account<'info, ReferralState>,
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct ResolveBet<'info> {
+    #[account(
+        mut,
         seeds = [b"bet", bet_id.as_bytes()],
         bump,
-        space = 8 + BetState::INIT_SPACE,
-        payer = creator,
     )]
     pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"creator_profile", bet.creator.as_ref()],
+        bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Option<Account<'info, HouseVault>>, // fixed-odds markets only
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub resolver: Signer<'info>, // the creator, or the platform arbiter on fixed-odds markets
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(bet_id: String)]
-pub struct PlaceBet<'info> {
+pub struct OverturnResolution<'info> {
     #[account(
         mut,
         seeds = [b"bet", bet_id.as_bytes()],
@@ -338,13 +3375,82 @@ pub struct PlaceBet<'info> {
     )]
     pub bet: Account<'info, BetState>,
     #[account(
-        init,
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"creator_profile", bet.creator.as_ref()],
+        bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Option<Account<'info, HouseVault>>, // fixed-odds markets only
+    pub arbiter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct FinalizeRandomClose<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    /// CHECK: address is pinned to the SlotHashes sysvar, which is read as raw bytes
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct UpdateBetWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct ClaimWinnings<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
         seeds = [b"user_bet", bet_id.as_bytes(), user.key().as_ref()],
         bump,
-        space = 8 + UserBetState::INIT_SPACE,
-        payer = user,
     )]
     pub user_bet: Account<'info, UserBetState>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -352,7 +3458,37 @@ pub struct PlaceBet<'info> {
 
 #[derive(Accounts)]
 #[instruction(bet_id: String)]
-pub struct ResolveBet<'info> {
+pub struct CloseLosingBet<'info> {
+    #[account(
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"user_bet", bet_id.as_bytes(), user.key().as_ref()],
+        bump,
+        close = user,
+    )]
+    pub user_bet: Account<'info, UserBetState>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct ClaimMakerFees<'info> {
     #[account(
         mut,
         seeds = [b"bet", bet_id.as_bytes()],
@@ -366,33 +3502,227 @@ pub struct ResolveBet<'info> {
 
 #[derive(Accounts)]
 #[instruction(bet_id: String)]
-pub struct ClaimWinnings<'info> {
+pub struct ReclaimCreatorBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct VoidUnresolvedBet<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        mut,
+        seeds = [b"creator_profile", bet.creator.as_ref()],
+        bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct PropagateParentResolution<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(address = bet.parent_market)]
+    pub parent_market: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        mut,
+        seeds = [b"user_bet", bet_id.as_bytes(), user.key().as_ref()],
+        bump,
+    )]
+    pub user_bet: Account<'info, UserBetState>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct SetFixedOdds<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    pub bookmaker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct SettleHouseReserve<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(parlay_id: u64)]
+pub struct PlaceParlay<'info> {
+    #[account(
+        init,
+        seeds = [b"parlay", user.key().as_ref(), parlay_id.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Parlay::INIT_SPACE,
+        payer = user,
+    )]
+    pub parlay: Account<'info, Parlay>,
+    #[account(
+        mut,
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        init_if_needed,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump,
+        space = 8 + UserProfile::INIT_SPACE,
+        payer = user,
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bet_id: String)]
+pub struct SetParlayEligible<'info> {
+    #[account(
+        mut,
+        seeds = [b"bet", bet_id.as_bytes()],
+        bump,
+    )]
+    pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleParlay<'info> {
+    #[account(
+        mut,
+        seeds = [b"parlay", parlay.user.as_ref(), parlay.parlay_id.to_le_bytes().as_ref()],
+        bump = parlay.bump,
+        close = user,
+    )]
+    pub parlay: Account<'info, Parlay>,
     #[account(
         mut,
-        seeds = [b"bet", bet_id.as_bytes()],
+        seeds = [b"house_vault"],
+        bump = house_vault.bump,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        seeds = [b"platform_config"],
         bump,
     )]
-    pub bet: Account<'info, BetState>,
+    pub platform_config: Account<'info, PlatformConfig>,
     #[account(
         mut,
-        seeds = [b"user_bet", bet_id.as_bytes(), user.key().as_ref()],
-        bump,
+        seeds = [b"user_profile", parlay.user.as_ref()],
+        bump = user_profile.bump,
     )]
-    pub user_bet: Account<'info, UserBetState>,
-    #[account(mut)]
-    pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub user_profile: Account<'info, UserProfile>,
+    /// CHECK: only receives lamports, pinned to the ticket holder
+    #[account(mut, address = parlay.user)]
+    pub user: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
 #[instruction(bet_id: String)]
-pub struct ClaimMakerFees<'info> {
+pub struct CancelBet<'info> {
     #[account(
         mut,
         seeds = [b"bet", bet_id.as_bytes()],
         bump,
+        close = creator,
     )]
     pub bet: Account<'info, BetState>,
+    #[account(
+        mut,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump,
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
     #[account(mut)]
     pub creator: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -400,30 +3730,64 @@ pub struct ClaimMakerFees<'info> {
 
 #[derive(Accounts)]
 #[instruction(bet_id: String)]
-pub struct CancelBet<'info> {
+pub struct GetBetStats<'info> {
     #[account(
-        mut,
         seeds = [b"bet", bet_id.as_bytes()],
         bump,
-        close = creator,
     )]
     pub bet: Account<'info, BetState>,
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 #[instruction(bet_id: String)]
-pub struct GetBetStats<'info> {
+pub struct PreviewFee<'info> {
     #[account(
         seeds = [b"bet", bet_id.as_bytes()],
         bump,
     )]
     pub bet: Account<'info, BetState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+    #[account(
+        seeds = [b"user_profile", user_profile.user.as_ref()],
+        bump = user_profile.bump,
+    )]
+    pub user_profile: Option<Account<'info, UserProfile>>,
 }
 
 // Account state definitions
+#[account]
+#[derive(InitSpace)]
+pub struct PlatformConfig {
+    pub owner: Pubkey,
+    pub max_end_time_extension: i64,
+    pub referral_fee_bps: u16, // basis points of the maker fee
+    pub max_maker_fee_bps: u16,
+    #[max_len(5)]
+    pub fee_tiers: Vec<FeeTier>, // sorted by ascending min_volume
+    pub arbiter: Pubkey,
+    pub dispute_window: i64,
+    pub resolution_grace_period: i64,
+    pub resolution_deadline: i64,
+    pub min_creator_bond: u64,
+    pub permissioned_creation: bool, // creators need a CreatorApproval when set
+    pub max_open_exposure_per_user: u64, // 0 = unlimited
+    pub max_odds_bps: u64,           // per-leg price cap on house-backed bets
+    pub max_parlay_payout_bps: u16,  // of the house vault's net asset value
+    pub max_market_reserve_bps: u16, // of the house vault's net asset value
+    pub bookmaker: Pubkey,           // only account allowed to run fixed-odds markets
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct FeeTier {
+    pub min_volume: u64,   // lifetime staked lamports needed to qualify
+    pub discount_bps: u16, // discount off the market's maker fee
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct BetState {
@@ -436,24 +3800,143 @@ pub struct BetState {
     pub option_a: String,
     #[max_len(100)]
     pub option_b: String,
+    pub start_time: i64,
     pub end_time: i64,
+    pub original_end_time: i64,
     pub total_amount_a: u64,
     pub total_amount_b: u64,
     pub is_resolved: bool,
-    pub winning_option: u8, // 0 = unresolved, 1 = option A, 2 = option B
+    pub winning_option: u8, // 0 = unresolved, 1 = option A, 2 = option B, DRAW_OUTCOME = draw
+    pub is_overturned: bool,
+    pub is_voided: bool,
     pub bump: u8,
     pub min_bet_amount: u64,
     pub max_bet_amount: u64,
-    #[max_len(50)]
-    pub category: String,
+    pub category: Pubkey, // Category PDA
     pub created_at: i64,
     pub resolved_at: i64,
     pub total_bettors: u64,
+    pub maker_fee_bps: u16,
     pub maker_fee_collected: u64,
+    pub creator_bond: u64,
+    pub slashed_amount: u64, // bond and fees forfeited by the creator, paid out to bettors
+    pub access_requirement: AccessRequirement,
+    pub max_position_per_user: u64, // 0 = unlimited
+    pub max_total_pool: u64,        // 0 = unlimited
+    pub max_imbalance_bps: u64,     // max larger/smaller side ratio, 10000 = 1:1, 0 = unlimited
+    pub cutoff_window: i64,
+    pub cutoff_mode: CutoffMode,
+    pub random_close_window: i64,
+    pub late_amounts_a: [u64; RANDOM_CLOSE_SLICES], // net stakes per random-close slice
+    pub late_amounts_b: [u64; RANDOM_CLOSE_SLICES],
+    pub random_close_slice: u8,
+    pub random_close_seed_slot: u64, // 0 until finalize_random_close commits to a slot
+    pub effective_end_time: i64,     // 0 until a random close is finalized
+    pub reveal_window: i64, // 0 = public market, otherwise seconds after end time to reveal
+    pub forfeit_unrevealed: bool,
+    pub total_committed: u64, // net stakes not yet revealed
+    pub market_type: MarketType,
+    pub resolved_value: i64, // scalar, line and bucketed markets only
+    #[max_len(9)]
+    pub bucket_bounds: Vec<i64>, // MAX_BUCKETS + 1 edges
+    #[max_len(8)]
+    pub bucket_amounts: Vec<u64>, // net stakes per bucket
+    pub parent_market: Pubkey, // default = not conditional
+    pub parent_outcome: u8,  // parent option this market is conditional on
+    pub parent_condition_met: bool,
+    pub parent_resolved_at: i64, // when the parent's result was propagated
+    pub child_count: u32,        // conditional markets linked to this one
+    pub fixed_odds_a_bps: u64,   // fixed-odds markets only, 0 = not offered
+    pub fixed_odds_b_bps: u64,
+    pub liability_a: u64, // locked payouts owed if option A wins
+    pub liability_b: u64,
+    pub house_reserve: u64, // house capital moved in to cover the worst outcome
+    pub house_settled: bool,
+    pub house_reserve_mark: u64, // what the house vault currently counts this market as worth
+    pub parlay_eligible: bool,   // platform has approved the market as a parlay leg
+    pub refund_fees_on_draw: bool, // creator's fees are shared back to bettors on a draw
+    #[max_len(8)]
+    pub resolution_weights_bps: Vec<u16>, // split resolutions only, indexed by option - 1
+    pub early_bonus: EarlyBonus,
+    #[max_len(8)]
+    pub weighted_pools: Vec<u64>, // early-weighted stakes per option, empty without a bonus
+    pub fee_curve: FeeCurve,
     #[max_len(300)]
     pub result_details: String,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum AccessRequirement {
+    Open,
+    Attestation { issuer: Pubkey },
+    TokenHolder { mint: Pubkey, min_amount: u64 },
+    NftCollection { collection: Pubkey },
+    MerkleAllowlist { root: [u8; 32] }, // leaves are sha256(bettor pubkey)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MarketLimits {
+    pub max_position_per_user: u64, // 0 = unlimited
+    pub max_total_pool: u64,        // net lamports across both sides, 0 = unlimited
+    pub max_imbalance_bps: u64,     // smaller side is floored at min_bet_amount, 0 = unlimited
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AntiSnipingConfig {
+    pub cutoff_window: i64, // seconds before end time the cutoff mode applies, 0 = off
+    pub cutoff_mode: CutoffMode,
+    pub random_close_window: i64, // seconds before end time the market may randomly close, 0 = off
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq, InitSpace)]
+pub enum MarketType {
+    Binary,
+    // Option A is long and option B short; the pool splits linearly on the resolved value
+    Scalar { lower_bound: i64, upper_bound: i64 },
+    // Option A is over and option B under the line, a result on the line pushes
+    Line { line: i64 },
+    // Each option is a bucket between consecutive `bucket_bounds`, numbered from 1
+    Bucketed { edge: BucketEdge },
+    // Bets are priced by the bookmaker and backed by the house vault
+    FixedOdds { bookmaker: Pubkey },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum FeeCurve {
+    Flat,
+    // Extra fee grows in a straight line from the start time to the end time
+    TimeToClose { max_extra_fee_bps: u16 },
+    // Extra fee grows with how lopsided a bet leaves the pool
+    Imbalance { max_extra_fee_bps: u16 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum EarlyBonus {
+    None,
+    // Bonus falls in a straight line from the start time to the end time
+    Linear { max_bonus_bps: u16 },
+    // Bonus halves every `half_life` seconds after the start time
+    Exponential { max_bonus_bps: u16, half_life: i64 },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum BucketEdge {
+    LowerInclusive, // a value on an edge belongs to the bucket above it
+    UpperInclusive, // a value on an edge belongs to the bucket below it
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CommitRevealConfig {
+    pub reveal_window: i64,       // seconds after end time bettors have to reveal
+    pub forfeit_unrevealed: bool, // false = unrevealed stakes are refunded after resolution
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum CutoffMode {
+    BlockBets,
+    FeeRamp { max_extra_fee_bps: u16 },
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct UserBetState {
@@ -467,6 +3950,148 @@ pub struct UserBetState {
     pub placed_at: i64,
     pub claimed_at: i64,
     pub original_amount: u64,
+    pub late_slice: u8, // 0 = outside the random-close window, otherwise slice index + 1
+    pub commitment: [u8; 32],
+    pub locked_odds_bps: u64, // fixed-odds markets only
+    pub potential_payout: u64,
+    pub weighted_amount: u64, // net stake after the early-bettor bonus
+    pub fee_bps: u16,         // fee rate applied to the latest placement
+    pub fees_paid: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorApproval {
+    pub creator: Pubkey,
+    pub approved_by: Pubkey,
+    pub approved_at: i64,
+    pub expires_at: i64, // 0 = never expires
+    pub is_revoked: bool,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub issuer: Pubkey,
+    pub subject: Pubkey,
+    pub issued_at: i64,
+    pub expires_at: i64, // 0 = never expires
+    pub is_revoked: bool,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Category {
+    pub id: u32,
+    #[max_len(50)]
+    pub name: String,
+    pub enabled: bool,
+    pub default_maker_fee_bps: u16,
+    pub default_min_bet_amount: u64,
+    pub default_max_bet_amount: u64,
+    pub market_count: u64,
+    pub total_volume: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserProfile {
+    pub user: Pubkey,
+    pub total_wagered: u64, // lifetime staked lamports, drives fee tiers
+    pub total_won: u64,
+    pub markets_entered: u64,
+    pub win_count: u64,
+    pub open_positions: u64,
+    pub open_exposure: u64, // gross stake in positions not yet settled
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    pub markets_created: u64,
+    pub markets_resolved: u64,
+    pub markets_resolved_on_time: u64,
+    pub markets_voided: u64,
+    pub markets_overturned: u64,
+    pub markets_cancelled: u64, // withdrawn before any bets, not held against the creator
+    pub total_resolution_delay: i64, // seconds past end time, summed over resolved markets
+    pub average_resolution_delay: i64,
+    pub total_volume_hosted: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralState {
+    pub referrer: Pubkey,
+    pub accrued_rewards: u64,
+    pub total_earned: u64,
+    pub total_claimed: u64,
+    pub referred_bets: u64,
+    pub referred_volume: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HouseVault {
+    pub total_liability: u64, // potential payouts owed on open parlays
+    pub total_staked: u64,
+    pub total_paid_out: u64,
+    pub total_market_reserves: u64, // unsettled fixed-odds markets at their current mark
+    pub total_fees_earned: u64,
+    pub total_pending_deposits: u64, // deposited but not yet priced into shares
+    pub lp_mint: Pubkey,
+    pub withdrawal_cooldown: i64,
+    pub lp_fee_bps: u16,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DepositRequest {
+    pub provider: Pubkey,
+    pub amount: u64, // lamports held in the house vault until shares are minted
+    pub requested_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalRequest {
+    pub provider: Pubkey,
+    pub shares: u64, // held in the provider's escrow token account until redeemed
+    pub requested_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Parlay {
+    pub user: Pubkey,
+    pub parlay_id: u64,
+    #[max_len(5)]
+    pub legs: Vec<ParlayLeg>,
+    pub stake: u64,
+    pub potential_payout: u64, // if every leg wins
+    pub placed_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct ParlayLeg {
+    pub market: Pubkey,
+    pub option: u8,
+    pub odds_bps: u64, // decimal odds locked at placement, 10000 = 1x
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -474,8 +4099,77 @@ pub struct BetStats {
     pub total_pool: u64,
     pub odds_a: u64,
     pub odds_b: u64,
+    pub bucket_probabilities: Vec<u64>, // bucketed markets only
     pub total_bettors: u64,
     pub time_remaining: i64,
+    pub time_until_open: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeeQuote {
+    pub fee_bps: u16,
+    pub curve_fee_bps: u16, // part of fee_bps from the market's fee curve
+    pub fee: u64,
+    pub net_amount: u64,
+    pub lifetime_volume: u64,
+}
+
+// Events
+#[event]
+pub struct EndTimeExtended {
+    pub bet_id: String,
+    pub old_end_time: i64,
+    pub new_end_time: i64,
+}
+
+#[event]
+pub struct BettingClosedEarly {
+    pub bet_id: String,
+    pub old_end_time: i64,
+    pub closed_at: i64,
+}
+
+#[event]
+pub struct ResolutionOverturned {
+    pub bet_id: String,
+    pub overturned_option: u8,
+    pub winning_option: u8,
+    pub resolved_value: i64,
+}
+
+#[event]
+pub struct UnresolvedBetVoided {
+    pub bet_id: String,
+    pub slashed_amount: u64,
+    pub voided_at: i64,
+}
+
+#[event]
+pub struct ParentResolutionPropagated {
+    pub bet_id: String,
+    pub parent_market: Pubkey,
+    pub condition_met: bool,
+}
+
+#[event]
+pub struct FixedOddsUpdated {
+    pub bet_id: String,
+    pub odds_a_bps: u64,
+    pub odds_b_bps: u64,
+}
+
+#[event]
+pub struct ParlaySettled {
+    pub user: Pubkey,
+    pub parlay_id: u64,
+    pub stake: u64,
+    pub payout: u64,
+}
+
+#[event]
+pub struct RandomCloseFinalized {
+    pub bet_id: String,
+    pub effective_end_time: i64,
 }
 
 // Custom error codes
@@ -521,4 +4215,677 @@ pub enum BettingError {
     InvalidDescription,
     #[msg("Invalid result details")]
     InvalidResultDetails,
+    #[msg("Betting has not opened yet")]
+    BettingNotOpen,
+    #[msg("Invalid platform configuration")]
+    InvalidPlatformConfig,
+    #[msg("End time extension exceeds platform limit")]
+    ExtensionTooLong,
+    #[msg("Cannot refer yourself")]
+    SelfReferral,
+    #[msg("Only the referrer can claim these rewards")]
+    UnauthorizedReferrer,
+    #[msg("Invalid fee schedule")]
+    InvalidFeeSchedule,
+    #[msg("Position did not lose")]
+    PositionNotLost,
+    #[msg("Only the platform arbiter can overturn resolutions")]
+    UnauthorizedArbiter,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+    #[msg("Creator bond below platform minimum")]
+    BondTooLow,
+    #[msg("No bond to reclaim")]
+    NoBondToReclaim,
+    #[msg("Bet has been voided")]
+    BetVoided,
+    #[msg("Position is not eligible for a refund")]
+    NotRefundable,
+    #[msg("Resolution deadline has not passed")]
+    ResolutionDeadlineNotPassed,
+    #[msg("Category is disabled")]
+    CategoryDisabled,
+    #[msg("Creator is not approved to create markets")]
+    CreatorNotApproved,
+    #[msg("Creator approval has expired")]
+    CreatorApprovalExpired,
+    #[msg("Invalid expiry")]
+    InvalidExpiry,
+    #[msg("Market requires an attestation from its issuer")]
+    AttestationRequired,
+    #[msg("Attestation is revoked or expired")]
+    AttestationInvalid,
+    #[msg("Market requires holding its token")]
+    TokenOwnershipRequired,
+    #[msg("Market requires holding an NFT from its collection")]
+    CollectionMembershipRequired,
+    #[msg("Bettor is not on the market allowlist")]
+    NotOnAllowlist,
+    #[msg("Top-ups must be on the same option")]
+    PositionOptionMismatch,
+    #[msg("Position exceeds the market's per-user limit")]
+    PositionLimitExceeded,
+    #[msg("Open exposure exceeds the platform's per-user limit")]
+    ExposureLimitExceeded,
+    #[msg("Invalid market limits")]
+    InvalidMarketLimits,
+    #[msg("Bet would exceed the market's pool cap")]
+    PoolCapExceeded,
+    #[msg("Bet would push the pools past the market's imbalance limit")]
+    PoolImbalanceExceeded,
+    #[msg("Invalid anti-sniping configuration")]
+    InvalidAntiSnipingConfig,
+    #[msg("Betting is closed during the cutoff window")]
+    CutoffWindowActive,
+    #[msg("Not allowed once the random-close window has started")]
+    RandomCloseWindowActive,
+    #[msg("Market does not use a random close")]
+    NotRandomClose,
+    #[msg("Random close already finalized")]
+    RandomCloseAlreadyFinalized,
+    #[msg("Random close has not been finalized")]
+    RandomCloseNotFinalized,
+    #[msg("Bet was placed after the random close")]
+    LateBetExcluded,
+    #[msg("Invalid commit-reveal configuration")]
+    InvalidCommitRevealConfig,
+    #[msg("Market requires a bet commitment")]
+    CommitmentRequired,
+    #[msg("Market does not use commit-reveal betting")]
+    NotCommitReveal,
+    #[msg("Reveal does not match the commitment")]
+    CommitmentMismatch,
+    #[msg("Bet has already been revealed")]
+    AlreadyRevealed,
+    #[msg("Reveal window has closed")]
+    RevealWindowClosed,
+    #[msg("Reveal window is still open")]
+    RevealWindowOpen,
+    #[msg("Scalar lower bound must be below upper bound")]
+    InvalidScalarBounds,
+    #[msg("Scalar, line and bucketed markets resolve to a numeric value")]
+    ResolvedValueRequired,
+    #[msg("Bucket bounds must be ascending and define 2 to 8 buckets")]
+    InvalidBucketBounds,
+    #[msg("Parent market must be open and the outcome must be one of its options")]
+    InvalidParentMarket,
+    #[msg("Bet is not conditional on a parent market")]
+    NotConditional,
+    #[msg("Parent market has not settled yet")]
+    ParentNotResolved,
+    #[msg("Parent resolution was already propagated")]
+    ParentAlreadyPropagated,
+    #[msg("Parlay legs must be distinct open markets with a valid option")]
+    InvalidParlayLeg,
+    #[msg("Parlay leg has no stake on the chosen option to price from")]
+    LegNotPriceable,
+    #[msg("House vault cannot cover this payout")]
+    InsufficientVaultLiquidity,
+    #[msg("Parlay legs are still pending")]
+    ParlayLegsPending,
+    #[msg("Fixed-odds markets cannot use commit-reveal, random close or imbalance limits")]
+    InvalidFixedOddsConfig,
+    #[msg("Bet is not a fixed-odds market")]
+    NotFixedOdds,
+    #[msg("Only the platform's approved bookmaker can run fixed-odds markets")]
+    UnauthorizedBookmaker,
+    #[msg("Odds must pay more than the stake")]
+    InvalidOdds,
+    #[msg("Bookmaker is not offering odds on this option")]
+    OddsNotOffered,
+    #[msg("House reserve already settled")]
+    HouseReserveSettled,
+    #[msg("Fixed-odds bets need the house vault")]
+    HouseVaultRequired,
+    #[msg("Liquidity cannot be withdrawn until the cooldown after the request has passed")]
+    WithdrawalCooldownActive,
+    #[msg("Resolution weights must cover every option and sum to 10000 bps")]
+    InvalidResolutionWeights,
+    #[msg("Early bonus is capped at 10000 bps and unavailable with random close or fixed odds")]
+    InvalidEarlyBonus,
+    #[msg("Fee curve exceeds the max fee rate or needs two visible sides")]
+    InvalidFeeCurve,
+    #[msg("Random close seed slot has not been produced yet")]
+    RandomCloseSeedPending,
+    #[msg("Market is not approved as an open parlay leg")]
+    ParlayLegNotEligible,
+    #[msg("Odds exceed the platform's cap")]
+    OddsTooHigh,
+    #[msg("Parlay payout exceeds the house vault's per-ticket cap")]
+    ParlayPayoutTooHigh,
+    #[msg("Market would draw more house reserve than the platform allows")]
+    MarketReserveCapExceeded,
+    #[msg("Market has conditional markets linked to it")]
+    MarketHasChildren,
+    #[msg("Market is approved as a parlay leg")]
+    MarketParlayEligible,
+    #[msg("Deposit cannot be priced into shares until its cooldown has passed")]
+    DepositCooldownActive,
+    #[msg("The deadline of a random-close market can't be moved")]
+    RandomCloseMarket,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_bet(market_type: MarketType) -> BetState {
+        BetState {
+            creator: Pubkey::default(),
+            bet_id: "test".to_string(),
+            description: String::new(),
+            option_a: "A".to_string(),
+            option_b: "B".to_string(),
+            start_time: 0,
+            end_time: 1000,
+            original_end_time: 1000,
+            total_amount_a: 0,
+            total_amount_b: 0,
+            is_resolved: true,
+            winning_option: 0,
+            is_overturned: false,
+            is_voided: false,
+            bump: 0,
+            min_bet_amount: 1,
+            max_bet_amount: u64::MAX,
+            category: Pubkey::default(),
+            created_at: 0,
+            resolved_at: 1000,
+            total_bettors: 0,
+            maker_fee_bps: 0,
+            maker_fee_collected: 0,
+            creator_bond: 0,
+            slashed_amount: 0,
+            access_requirement: AccessRequirement::Open,
+            max_position_per_user: 0,
+            max_total_pool: 0,
+            max_imbalance_bps: 0,
+            cutoff_window: 0,
+            cutoff_mode: CutoffMode::BlockBets,
+            random_close_window: 0,
+            late_amounts_a: [0; RANDOM_CLOSE_SLICES],
+            late_amounts_b: [0; RANDOM_CLOSE_SLICES],
+            random_close_slice: 0,
+            random_close_seed_slot: 0,
+            effective_end_time: 0,
+            reveal_window: 0,
+            forfeit_unrevealed: false,
+            total_committed: 0,
+            market_type,
+            resolved_value: 0,
+            bucket_bounds: Vec::new(),
+            bucket_amounts: Vec::new(),
+            parent_market: Pubkey::default(),
+            parent_outcome: 0,
+            parent_condition_met: false,
+            parent_resolved_at: 0,
+            child_count: 0,
+            fixed_odds_a_bps: 0,
+            fixed_odds_b_bps: 0,
+            liability_a: 0,
+            liability_b: 0,
+            house_reserve: 0,
+            house_settled: false,
+            house_reserve_mark: 0,
+            parlay_eligible: false,
+            refund_fees_on_draw: false,
+            resolution_weights_bps: Vec::new(),
+            early_bonus: EarlyBonus::None,
+            weighted_pools: Vec::new(),
+            fee_curve: FeeCurve::Flat,
+            result_details: String::new(),
+        }
+    }
+
+    fn bucketed_bet(bucket_amounts: Vec<u64>) -> BetState {
+        let mut bet = test_bet(MarketType::Bucketed {
+            edge: BucketEdge::LowerInclusive,
+        });
+        bet.bucket_bounds = (0..=bucket_amounts.len() as i64).map(|i| i * 10).collect();
+        bet.bucket_amounts = bucket_amounts;
+        bet
+    }
+
+    /// Sum of what every option's backers are owed, which must never exceed the pool
+    fn total_option_payouts(bet: &BetState) -> u64 {
+        let total_pool = total_pool(bet);
+        (1..=option_count(bet) as u8)
+            .filter(|&option| option_pool(bet, option) > 0)
+            .map(|option| {
+                (total_pool as u128 * calculate_payout_weight_bps(bet, option) as u128 / 10000)
+                    as u64
+            })
+            .sum()
+    }
+
+    #[test]
+    fn split_weights_cover_the_pool() {
+        let mut bet = bucketed_bet(vec![300, 500, 200]);
+        bet.resolution_weights_bps = vec![2500, 5000, 2500];
+
+        assert_eq!(calculate_split_weight_bps(&bet, 1), 2500);
+        assert_eq!(calculate_split_weight_bps(&bet, 2), 5000);
+        assert_eq!(calculate_split_weight_bps(&bet, 3), 2500);
+        assert_eq!(total_option_payouts(&bet), total_pool(&bet));
+    }
+
+    #[test]
+    fn split_weights_rescale_over_staked_options() {
+        // Nobody backed bucket 2, so its weight is shared by buckets 1 and 3
+        let mut bet = bucketed_bet(vec![400, 0, 600]);
+        bet.resolution_weights_bps = vec![3000, 4000, 3000];
+
+        assert_eq!(calculate_split_weight_bps(&bet, 1), 5000);
+        assert_eq!(calculate_split_weight_bps(&bet, 2), 0);
+        assert_eq!(calculate_split_weight_bps(&bet, 3), 5000);
+        assert_eq!(total_option_payouts(&bet), total_pool(&bet));
+    }
+
+    #[test]
+    fn split_weights_round_down_within_the_pool() {
+        let mut bet = bucketed_bet(vec![333, 333, 334]);
+        bet.resolution_weights_bps = vec![3333, 3333, 3334];
+        let paid = total_option_payouts(&bet);
+        assert!(paid <= total_pool(&bet) && paid + 3 >= total_pool(&bet));
+
+        // Only zero-weight options were backed, so nothing is owed
+        let mut bet = bucketed_bet(vec![0, 500, 0]);
+        bet.resolution_weights_bps = vec![5000, 0, 5000];
+        assert_eq!(calculate_split_weight_bps(&bet, 2), 0);
+        assert_eq!(calculate_split_weight_bps(&bet, 0), 0);
+    }
+
+    #[test]
+    fn refunds_share_the_slashed_amount_pro_rata() {
+        assert_eq!(calculate_refund(500, 0, 0), 500);
+        assert_eq!(calculate_refund(300, 1000, 0), 300);
+        assert_eq!(calculate_refund(300, 1000, 200), 360);
+
+        // Every bettor's refund together never exceeds the pool plus what was slashed
+        let stakes = [333, 333, 334];
+        let total_pool: u64 = stakes.iter().sum();
+        let refunded: u64 = stakes
+            .iter()
+            .map(|&amount| calculate_refund(amount, total_pool, 101))
+            .sum();
+        assert!(refunded <= total_pool + 101 && refunded + stakes.len() as u64 >= total_pool + 101);
+    }
+
+    fn merkle_parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn merkle_proof_accepts_members_only() {
+        let leaves: Vec<[u8; 32]> = (1..=4u8)
+            .map(|i| hashv(&[Pubkey::new_from_array([i; 32]).as_ref()]).to_bytes())
+            .collect();
+        let left = merkle_parent(leaves[0], leaves[1]);
+        let right = merkle_parent(leaves[2], leaves[3]);
+        let root = merkle_parent(left, right);
+
+        assert!(verify_merkle_proof(leaves[0], &[leaves[1], right], root));
+        assert!(verify_merkle_proof(leaves[3], &[leaves[2], left], root));
+        assert!(!verify_merkle_proof(leaves[0], &[leaves[2], right], root));
+        assert!(!verify_merkle_proof(leaves[0], &[leaves[1]], root));
+
+        let outsider = hashv(&[Pubkey::new_from_array([9; 32]).as_ref()]).to_bytes();
+        assert!(!verify_merkle_proof(outsider, &[leaves[1], right], root));
+        // A single-leaf tree is its own root
+        assert!(verify_merkle_proof(leaves[0], &[], leaves[0]));
+    }
+
+    fn test_user_bet(option: u8, amount: u64) -> UserBetState {
+        UserBetState {
+            user: Pubkey::default(),
+            bet_id: "test".to_string(),
+            option,
+            amount,
+            is_claimed: false,
+            bump: 0,
+            placed_at: 0,
+            claimed_at: 0,
+            original_amount: amount,
+            late_slice: 0,
+            commitment: [0; 32],
+            locked_odds_bps: 0,
+            potential_payout: 0,
+            weighted_amount: amount,
+            fee_bps: 0,
+            fees_paid: 0,
+        }
+    }
+
+    #[test]
+    fn random_close_excludes_only_bets_after_the_chosen_slice() {
+        let mut bet = test_bet(MarketType::Binary);
+        bet.random_close_window = 800;
+
+        assert_eq!(random_close_slice(&bet, 199), None);
+        assert_eq!(random_close_slice(&bet, 200), Some(0));
+        assert_eq!(random_close_slice(&bet, 550), Some(3));
+        assert_eq!(random_close_slice(&bet, 999), Some(7));
+        // Bets after the end time are clamped to the last slice
+        assert_eq!(random_close_slice(&bet, 5000), Some(7));
+
+        let mut user_bet = test_user_bet(1, 100);
+        for (late_slice, is_excluded) in [(0, false), (3, false), (4, false), (5, true), (8, true)]
+        {
+            user_bet.late_slice = late_slice;
+            // Nothing is excluded until the close has been drawn
+            assert!(!is_excluded_by_random_close(&bet, &user_bet));
+            bet.random_close_slice = 3;
+            bet.effective_end_time = 600;
+            assert_eq!(is_excluded_by_random_close(&bet, &user_bet), is_excluded);
+            bet.effective_end_time = 0;
+        }
+
+        // Without a random close every bet counts
+        bet.random_close_window = 0;
+        assert_eq!(random_close_slice(&bet, 999), None);
+    }
+
+    #[test]
+    fn committed_stakes_block_cancellation_and_are_refunded() {
+        // One hidden commitment, nothing in either pool yet
+        let mut bet = test_bet(MarketType::Binary);
+        bet.is_resolved = false;
+        bet.reveal_window = 100;
+        bet.total_committed = 400;
+        bet.total_bettors = 1;
+        assert_eq!(
+            validate_cancellation(&bet),
+            Err(BettingError::BetsAlreadyPlaced.into())
+        );
+
+        let user_bet = test_user_bet(0, 400);
+        assert_eq!(
+            calculate_claimable_refund(&bet, &user_bet),
+            Err(BettingError::NotRefundable.into())
+        );
+
+        // An unrevealed stake comes back once the market resolves, unless it is forfeit
+        bet.is_resolved = true;
+        assert_eq!(calculate_claimable_refund(&bet, &user_bet), Ok(400));
+        bet.forfeit_unrevealed = true;
+        assert_eq!(
+            calculate_claimable_refund(&bet, &user_bet),
+            Err(BettingError::NotRefundable.into())
+        );
+
+        // A revealed stake on a voided market shares the slashed bond
+        bet.is_resolved = false;
+        bet.is_voided = true;
+        bet.total_amount_a = 400;
+        bet.total_amount_b = 600;
+        bet.slashed_amount = 100;
+        assert_eq!(
+            calculate_claimable_refund(&bet, &test_user_bet(1, 400)),
+            Ok(440)
+        );
+    }
+
+    #[test]
+    fn scalar_weights_split_the_pool_linearly() {
+        let mut bet = test_bet(MarketType::Scalar {
+            lower_bound: 100,
+            upper_bound: 200,
+        });
+        bet.total_amount_a = 600;
+        bet.total_amount_b = 400;
+
+        for (resolved_value, long_bps) in
+            [(100, 0), (125, 2500), (200, 10000), (50, 0), (900, 10000)]
+        {
+            bet.resolved_value = resolved_value;
+            assert_eq!(calculate_payout_weight_bps(&bet, 1), long_bps);
+            assert_eq!(calculate_payout_weight_bps(&bet, 2), 10000 - long_bps);
+            assert_eq!(total_option_payouts(&bet), total_pool(&bet));
+        }
+    }
+
+    #[test]
+    fn scalar_weights_handle_extreme_bounds_and_one_sided_pools() {
+        let mut bet = test_bet(MarketType::Scalar {
+            lower_bound: i64::MIN,
+            upper_bound: i64::MAX,
+        });
+        bet.total_amount_a = 500;
+        bet.total_amount_b = 500;
+
+        bet.resolved_value = 0;
+        assert_eq!(calculate_payout_weight_bps(&bet, 1), 5000);
+        bet.resolved_value = i64::MAX;
+        assert_eq!(calculate_payout_weight_bps(&bet, 1), 10000);
+        bet.resolved_value = i64::MIN;
+        assert_eq!(calculate_payout_weight_bps(&bet, 1), 0);
+
+        // A side nobody backed can't be owed anything
+        bet.resolved_value = 0;
+        bet.total_amount_b = 0;
+        assert_eq!(calculate_payout_weight_bps(&bet, 1), 10000);
+        assert_eq!(total_option_payouts(&bet), total_pool(&bet));
+        bet.total_amount_a = 0;
+        bet.total_amount_b = 500;
+        assert_eq!(calculate_payout_weight_bps(&bet, 2), 10000);
+    }
+
+    #[test]
+    fn winning_bucket_respects_the_edge_rule() {
+        let bet = bucketed_bet(vec![100, 100, 100]);
+
+        assert_eq!(winning_bucket(&bet, 5, BucketEdge::LowerInclusive), 1);
+        assert_eq!(winning_bucket(&bet, 10, BucketEdge::LowerInclusive), 2);
+        assert_eq!(winning_bucket(&bet, 10, BucketEdge::UpperInclusive), 1);
+        assert_eq!(winning_bucket(&bet, 20, BucketEdge::UpperInclusive), 2);
+        assert_eq!(winning_bucket(&bet, 21, BucketEdge::UpperInclusive), 3);
+        // Values outside the range land in the first or last bucket
+        assert_eq!(winning_bucket(&bet, -100, BucketEdge::LowerInclusive), 1);
+        assert_eq!(winning_bucket(&bet, 1000, BucketEdge::LowerInclusive), 3);
+    }
+
+    #[test]
+    fn winning_bucket_falls_back_to_the_nearest_staked_bucket() {
+        let bet = bucketed_bet(vec![100, 0, 0, 100, 0]);
+
+        assert_eq!(winning_bucket(&bet, 15, BucketEdge::LowerInclusive), 1);
+        assert_eq!(winning_bucket(&bet, 25, BucketEdge::LowerInclusive), 4);
+        assert_eq!(winning_bucket(&bet, 45, BucketEdge::LowerInclusive), 4);
+
+        // With no stakes anywhere the containing bucket still wins
+        let bet = bucketed_bet(vec![0, 0, 0]);
+        assert_eq!(winning_bucket(&bet, 15, BucketEdge::LowerInclusive), 2);
+    }
+
+    #[test]
+    fn parlay_legs_multiply_the_payout() {
+        let leg = |option, odds_bps| ParlayLeg {
+            market: Pubkey::default(),
+            option,
+            odds_bps,
+        };
+        let mut market = test_bet(MarketType::Binary);
+        market.winning_option = 1;
+        market.total_amount_a = 500;
+        market.total_amount_b = 500;
+
+        // Two winning legs at 2x and 1.5x
+        let payout = apply_parlay_leg(1000, &leg(1, 20000), &market).unwrap();
+        assert_eq!(
+            apply_parlay_leg(payout, &leg(1, 15000), &market),
+            Some(3000)
+        );
+        assert_eq!(apply_parlay_leg(payout, &leg(2, 15000), &market), None);
+
+        // A drawn leg leaves the price untouched
+        market.winning_option = DRAW_OUTCOME;
+        assert_eq!(apply_parlay_leg(1000, &leg(2, 30000), &market), Some(1000));
+
+        // A split resolution pays each side its share of the leg's odds
+        market.winning_option = 0;
+        market.resolution_weights_bps = vec![7500, 2500];
+        assert_eq!(apply_parlay_leg(1000, &leg(1, 20000), &market), Some(1500));
+        assert_eq!(apply_parlay_leg(1000, &leg(2, 20000), &market), Some(500));
+    }
+
+    #[test]
+    fn lp_shares_resist_first_deposit_inflation() {
+        // An attacker mints 1 share then donates to inflate the price
+        let attacker_shares = calculate_lp_shares(1, 0, 0);
+        let donation = 10_000_000_000;
+        let victim_shares = calculate_lp_shares(5_000_000_000, attacker_shares, donation + 1);
+        assert!(victim_shares > 0);
+
+        // The attacker can't redeem more than they put in
+        let supply = attacker_shares + victim_shares;
+        let net_asset_value = donation + 1 + 5_000_000_000;
+        assert!(calculate_lp_redemption(attacker_shares, supply, net_asset_value) < donation + 1);
+    }
+
+    #[test]
+    fn lp_shares_round_trip_without_profit() {
+        assert_eq!(calculate_lp_shares(1_000_000_000, 0, 0), 1_000_000_000);
+
+        let supply = 2_000_000_000;
+        let net_asset_value = 3_000_000_000;
+        let shares = calculate_lp_shares(1_000_000_000, supply, net_asset_value);
+        let redeemed =
+            calculate_lp_redemption(shares, supply + shares, net_asset_value + 1_000_000_000);
+        assert!(redeemed <= 1_000_000_000);
+
+        // Redeeming everything never pays more than the vault is worth
+        assert_eq!(calculate_lp_redemption(supply, supply, 5), 5);
+        assert_eq!(calculate_lp_redemption(0, supply, net_asset_value), 0);
+    }
+
+    #[test]
+    fn house_reserve_is_marked_at_what_the_market_returns() {
+        let mut bet = test_bet(MarketType::FixedOdds {
+            bookmaker: Pubkey::default(),
+        });
+        bet.total_amount_a = 1000;
+        bet.total_amount_b = 500;
+        bet.liability_a = 2500;
+        bet.liability_b = 1200;
+        bet.house_reserve = 2000;
+        bet.house_reserve_mark = 2000;
+        let mut house_vault = HouseVault {
+            total_liability: 0,
+            total_staked: 0,
+            total_paid_out: 0,
+            total_market_reserves: 7000, // another market is marked at 5000
+            total_fees_earned: 0,
+            total_pending_deposits: 0,
+            lp_mint: Pubkey::default(),
+            withdrawal_cooldown: 0,
+            lp_fee_bps: 0,
+            bump: 0,
+        };
+
+        bet.winning_option = 1;
+        assert_eq!(house_surplus(&bet), 1000);
+        mark_house_reserve(&mut bet, &mut house_vault);
+        assert_eq!(house_vault.total_market_reserves, 6000);
+
+        // An overturn re-marks from the previous mark, not the original reserve
+        bet.winning_option = 2;
+        mark_house_reserve(&mut bet, &mut house_vault);
+        assert_eq!(bet.house_reserve_mark, 2300);
+        assert_eq!(house_vault.total_market_reserves, 7300);
+
+        // Drawn and voided markets return every stake, so the house keeps its reserve
+        bet.winning_option = DRAW_OUTCOME;
+        assert_eq!(house_surplus(&bet), 2000);
+        bet.winning_option = 1;
+        bet.is_voided = true;
+        mark_house_reserve(&mut bet, &mut house_vault);
+        assert_eq!(house_vault.total_market_reserves, 7000);
+    }
+
+    #[test]
+    fn early_bonus_decays_to_nothing_by_the_end_time() {
+        let mut bet = test_bet(MarketType::Binary);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 0), 1000);
+
+        bet.early_bonus = EarlyBonus::Linear {
+            max_bonus_bps: 5000,
+        };
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 0), 1500);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 500), 1250);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 1000), 1000);
+        // Bets outside the market's window are clamped to it
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, -50), 1500);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 2000), 1000);
+    }
+
+    #[test]
+    fn exponential_early_bonus_halves_each_half_life() {
+        let mut bet = test_bet(MarketType::Binary);
+        bet.early_bonus = EarlyBonus::Exponential {
+            max_bonus_bps: 8000,
+            half_life: 100,
+        };
+
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 0), 1800);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 50), 1600);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 100), 1400);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 200), 1200);
+        assert_eq!(calculate_early_weighted_amount(&bet, 1000, 1000), 1000);
+        assert_eq!(
+            calculate_early_weighted_amount(&bet, u64::MAX, 0),
+            (u64::MAX as u128 * 18000 / 10000) as u64
+        );
+    }
+
+    #[test]
+    fn weighted_winnings_stay_within_the_option_payout() {
+        let mut bet = test_bet(MarketType::Binary);
+        bet.early_bonus = EarlyBonus::Linear {
+            max_bonus_bps: 10000,
+        };
+        let stakes = [(700, 0), (300, 400), (500, 999)];
+        let weighted: Vec<u64> = stakes
+            .iter()
+            .map(|&(amount, now)| calculate_early_weighted_amount(&bet, amount, now))
+            .collect();
+        let weighted_pool: u64 = weighted.iter().sum();
+        bet.total_amount_a = stakes.iter().map(|&(amount, _)| amount).sum();
+        bet.total_amount_b = 900;
+
+        let option_payout = total_pool(&bet);
+        let paid: u64 = weighted
+            .iter()
+            .map(|&amount| calculate_winnings(amount, weighted_pool, option_payout).unwrap())
+            .sum();
+        assert!(paid <= option_payout && paid + stakes.len() as u64 >= option_payout);
+        // The earliest stake earns more per lamport than the latest
+        assert!(weighted[0] * 500 > weighted[2] * 700);
+    }
+
+    #[test]
+    fn combined_fee_is_clamped_to_the_max_rate() {
+        let mut bet = test_bet(MarketType::Binary);
+        bet.maker_fee_bps = 800;
+        bet.cutoff_window = 100;
+        bet.cutoff_mode = CutoffMode::FeeRamp {
+            max_extra_fee_bps: 1000,
+        };
+        bet.fee_curve = FeeCurve::TimeToClose {
+            max_extra_fee_bps: 1000,
+        };
+
+        let (fee_bps, curve_fee_bps) = calculate_fee_bps(&bet, &[], 0, 1, 1000, 999);
+        assert_eq!(fee_bps, MAX_FEE_RATE as u16);
+        assert_eq!(curve_fee_bps, 0);
+        let (fee_bps, curve_fee_bps) = calculate_fee_bps(&bet, &[], 0, 1, 1000, 0);
+        assert_eq!((fee_bps, curve_fee_bps), (800, 0));
+        let (fee_bps, curve_fee_bps) = calculate_fee_bps(&bet, &[], 0, 1, 1000, 100);
+        assert_eq!((fee_bps, curve_fee_bps), (900, 100));
+    }
 }
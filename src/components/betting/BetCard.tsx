@@ -39,6 +39,7 @@ export function BettingCard({ bet }: { bet: any }) {
     isUserBetCreator,
     canUserClaimWinnings,
     canUserClaimMakerFees,
+    categoryName,
     betTimeRemaining,
   } = useBettingProgramAccount({ betId });
   console.log("BettingCard", bet);
@@ -124,6 +125,7 @@ export function BettingCard({ bet }: { bet: any }) {
 
   const getWinningOptionText = () => {
     if (!isResolved) return null;
+    if (betData.winningOption === 255) return "Draw";
     return betData.winningOption === 1 ? betData.optionA : betData.optionB;
   };
 
@@ -142,7 +144,7 @@ export function BettingCard({ bet }: { bet: any }) {
               <span className="text-xs bg-white/20 px-2 py-1 rounded-full">ID: {betId}</span>
               {getBetStatus()}
               <Badge variant="outline" className="border-gray-400 text-gray-300 hover:bg-gray-700">
-                {betData.category}
+                {categoryName}
               </Badge>
             </CardDescription>
           </div>
@@ -15,7 +15,7 @@ import { BettingCard } from "./BetCard";
 import { useBettingProgram } from "./betting-data-access";
 
 export function BettingList() {
-  const { allBets, getProgramAccount } = useBettingProgram();
+  const { allBets, getProgramAccount, categoryNames } = useBettingProgram();
 
   // Filter and sort states
   const [searchTerm, setSearchTerm] = useState("");
@@ -45,12 +45,18 @@ export function BettingList() {
     [],
   );
 
+  // Bets store their Category PDA, look up its name for searching, filtering and sorting
+  const getCategoryName = useMemo(
+    () => (bet) => categoryNames.get(bet.account.category.toBase58()) ?? "",
+    [categoryNames],
+  );
+
   // Get unique categories for filter
   const categories = useMemo(() => {
     if (!allBets.data) return [];
-    const uniqueCategories = [...new Set(allBets.data.map((bet) => bet.account.category))];
+    const uniqueCategories = [...new Set(allBets.data.map(getCategoryName))];
     return uniqueCategories.filter(Boolean);
-  }, [allBets.data]);
+  }, [allBets.data, getCategoryName]);
 
   // Memoized filtered and sorted data
   const filteredAndSortedBets = useMemo(() => {
@@ -65,13 +71,13 @@ export function BettingList() {
         account.description?.toLowerCase().includes(searchTerm.toLowerCase()) ||
         account.optionA?.toLowerCase().includes(searchTerm.toLowerCase()) ||
         account.optionB?.toLowerCase().includes(searchTerm.toLowerCase()) ||
-        account.category?.toLowerCase().includes(searchTerm.toLowerCase());
+        getCategoryName(bet).toLowerCase().includes(searchTerm.toLowerCase());
 
       // Status filter
       const matchesStatus = statusFilter === "all" || status === statusFilter;
 
       // Category filter
-      const matchesCategory = categoryFilter === "all" || account.category === categoryFilter;
+      const matchesCategory = categoryFilter === "all" || getCategoryName(bet) === categoryFilter;
 
       return matchesSearch && matchesStatus && matchesCategory;
     });
@@ -100,8 +106,8 @@ export function BettingList() {
           bValue = bnToNumber(accountB.endTime);
           break;
         case "category":
-          aValue = accountA.category || "";
-          bValue = accountB.category || "";
+          aValue = getCategoryName(a);
+          bValue = getCategoryName(b);
           break;
         case "created_at":
         default:
@@ -118,7 +124,7 @@ export function BettingList() {
     });
 
     return filtered;
-  }, [allBets.data, searchTerm, statusFilter, categoryFilter, sortBy, sortOrder, getBetStatus]);
+  }, [allBets.data, searchTerm, statusFilter, categoryFilter, sortBy, sortOrder, getBetStatus, getCategoryName]);
 
   // Loading state
   if (getProgramAccount.isLoading) {
@@ -11,7 +11,7 @@ import { useState } from "react";
 import { useBettingProgram } from "./betting-data-access";
 
 export function BettingCreate() {
-  const { createBet } = useBettingProgram();
+  const { createBet, categories } = useBettingProgram();
 
   const initialState = {
     description: "",
@@ -52,7 +52,7 @@ export function BettingCreate() {
       optionA: optionA.trim(),
       optionB: optionB.trim(),
       endTime: endTimeTimestamp,
-      category: category.trim(),
+      categoryId: Number(category),
       minBetAmount: parseFloat(minBetAmount) * 1e9,
       maxBetAmount: parseFloat(maxBetAmount) * 1e9,
     });
@@ -60,7 +60,11 @@ export function BettingCreate() {
     setFormData(initialState);
   };
 
-  const categories = ["Sports", "Politics", "Entertainment", "Technology", "Finance", "Weather", "Other"];
+  // Only categories the platform owner has registered and left enabled can take new markets
+  const enabledCategories = (categories.data ?? [])
+    .map((category) => category.account)
+    .filter((category) => category.enabled);
+  const selectedCategoryName = enabledCategories.find((category) => String(category.id) === formData.category)?.name;
 
   return (
     <div className="min-h-screen bg-background">
@@ -144,9 +148,9 @@ export function BettingCreate() {
                     <SelectValue placeholder="Select a category" />
                   </SelectTrigger>
                   <SelectContent>
-                    {categories.map((cat) => (
-                      <SelectItem key={cat} value={cat}>
-                        {cat}
+                    {enabledCategories.map((category) => (
+                      <SelectItem key={category.id} value={String(category.id)}>
+                        {category.name}
                       </SelectItem>
                     ))}
                   </SelectContent>
@@ -224,7 +228,7 @@ export function BettingCreate() {
                 </div>
                 <div className="flex justify-between">
                   <span className="text-muted-foreground">Category:</span>
-                  <span className="font-medium">{formData.category || "Not selected"}</span>
+                  <span className="font-medium">{selectedCategoryName || "Not selected"}</span>
                 </div>
                 <div className="flex justify-between">
                   <span className="text-muted-foreground">Options:</span>
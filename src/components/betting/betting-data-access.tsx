@@ -19,7 +19,7 @@ interface CreateBetParams {
   endTime: number;
   minBetAmount: number;
   maxBetAmount: number;
-  category: string;
+  categoryId: number; // id of a Category registered by the platform owner
 }
 
 interface PlaceBetParams {
@@ -38,14 +38,27 @@ interface BetStats {
   totalPool: BN;
   oddsA: BN;
   oddsB: BN;
+  bucketProbabilities: BN[];
   totalBettors: BN;
   timeRemaining: BN;
+  timeUntilOpen: BN;
 }
 
 // Constants
 const LAMPORTS_PER_SOL = 1_000_000_000;
 const DEFAULT_MIN_BET = 0.01 * LAMPORTS_PER_SOL; // 0.01 SOL
 const DEFAULT_MAX_BET = 100 * LAMPORTS_PER_SOL; // 100 SOL
+const DRAW_OUTCOME = 255; // winningOption of a drawn or pushed market
+
+// Settings for a plain public binary market, the program's defaults for everything else
+const OPEN_MARKET_SETTINGS = {
+  accessRequirement: { open: {} },
+  limits: { maxPositionPerUser: new BN(0), maxTotalPool: new BN(0), maxImbalanceBps: new BN(0) },
+  antiSniping: { cutoffWindow: new BN(0), cutoffMode: { blockBets: {} }, randomCloseWindow: new BN(0) },
+  marketType: { binary: {} },
+  earlyBonus: { none: {} },
+  feeCurve: { flat: {} },
+};
 
 // Helper functions
 const generateBetId = (description: string, timestamp: number): string => {
@@ -53,11 +66,20 @@ const generateBetId = (description: string, timestamp: number): string => {
   return Buffer.from(combined).toString("base64").slice(0, 32);
 };
 
+const findCategoryPda = (categoryId: number, programId: PublicKey): PublicKey => {
+  const id = Buffer.alloc(4);
+  id.writeUInt32LE(categoryId);
+  return PublicKey.findProgramAddressSync([Buffer.from("category"), id], programId)[0];
+};
+
+const findHouseVaultPda = (programId: PublicKey): PublicKey =>
+  PublicKey.findProgramAddressSync([Buffer.from("house_vault")], programId)[0];
+
 const validateBetParams = (params: CreateBetParams): void => {
   if (!params.description.trim()) throw new Error("Description is required");
   if (!params.optionA.trim()) throw new Error("Option A is required");
   if (!params.optionB.trim()) throw new Error("Option B is required");
-  if (!params.category.trim()) throw new Error("Category is required");
+  if (!Number.isInteger(params.categoryId) || params.categoryId < 0) throw new Error("Category is required");
   if (params.endTime <= Date.now()) throw new Error("End time must be in the future");
   if (params.minBetAmount <= 0) throw new Error("Minimum bet amount must be positive");
   if (params.maxBetAmount < params.minBetAmount) throw new Error("Maximum bet amount must be >= minimum bet amount");
@@ -83,11 +105,41 @@ export function useBettingProgram() {
     refetchInterval: 10000,
   });
 
+  // Accounts the IDL can't resolve on its own for a public market without a referrer
+  const placeBetAccounts = (bet: { category: PublicKey; marketType: object }) => ({
+    category: bet.category,
+    referral: null,
+    attestation: null,
+    accessTokenAccount: null,
+    accessMetadata: null,
+    houseVault: "fixedOdds" in bet.marketType ? findHouseVaultPda(programId) : null,
+    user: provider.wallet.publicKey,
+  });
+
   const getProgramAccount = useQuery({
     queryKey: ["get-program-account", { cluster }],
     queryFn: () => connection.getParsedAccountInfo(programId),
   });
 
+  const platformConfig = useQuery({
+    queryKey: ["betting", "platform-config", { cluster }],
+    queryFn: () =>
+      program.account.platformConfig.fetch(
+        PublicKey.findProgramAddressSync([Buffer.from("platform_config")], programId)[0],
+      ),
+  });
+
+  const categories = useQuery({
+    queryKey: ["betting", "categories", { cluster }],
+    queryFn: () => program.account.category.all(),
+  });
+
+  // Markets store their Category PDA, map it back to the category's name for display
+  const categoryNames = useMemo(
+    () => new Map((categories.data ?? []).map((category) => [category.publicKey.toBase58(), category.account.name])),
+    [categories.data],
+  );
+
   const createBet = useMutation({
     mutationKey: ["betting", "create-bet", { cluster }],
     mutationFn: async (params: CreateBetParams) => {
@@ -96,7 +148,14 @@ export function useBettingProgram() {
       const timestamp = Date.now();
       const betId = generateBetId(params.description, timestamp);
 
-      const [betPda] = PublicKey.findProgramAddressSync([Buffer.from("bet"), Buffer.from(betId)], programId);
+      const config = platformConfig.data ?? (await platformConfig.refetch()).data;
+      if (!config) throw new Error("Platform is not initialized");
+      const creatorApproval = config.permissionedCreation
+        ? PublicKey.findProgramAddressSync(
+            [Buffer.from("creator_approval"), provider.wallet.publicKey.toBuffer()],
+            programId,
+          )[0]
+        : null;
 
       return program.methods
         .createBet(
@@ -104,12 +163,27 @@ export function useBettingProgram() {
           params.description,
           params.optionA,
           params.optionB,
+          new BN(0), // open immediately
           new BN(Math.floor(params.endTime / 1000)), // Convert to seconds
           new BN(params.minBetAmount || DEFAULT_MIN_BET),
           new BN(params.maxBetAmount || DEFAULT_MAX_BET),
-          params.category,
+          null, // category's default maker fee
+          config.minCreatorBond,
+          OPEN_MARKET_SETTINGS.accessRequirement,
+          OPEN_MARKET_SETTINGS.limits,
+          OPEN_MARKET_SETTINGS.antiSniping,
+          null, // public bets
+          OPEN_MARKET_SETTINGS.marketType,
+          [],
+          0, // not conditional
+          false,
+          OPEN_MARKET_SETTINGS.earlyBonus,
+          OPEN_MARKET_SETTINGS.feeCurve,
         )
-        .accounts({
+        .accountsPartial({
+          category: findCategoryPda(params.categoryId, programId),
+          creatorApproval,
+          parentMarket: null,
           creator: provider.wallet.publicKey,
         })
         .rpc();
@@ -133,17 +207,11 @@ export function useBettingProgram() {
       if (amount <= 0) throw new Error("Amount must be positive");
 
       const [betPda] = PublicKey.findProgramAddressSync([Buffer.from("bet"), Buffer.from(betId)], programId);
-
-      const [userBetPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("user_bet"), Buffer.from(betId), provider.wallet.publicKey.toBuffer()],
-        programId,
-      );
+      const bet = await program.account.betState.fetch(betPda);
 
       return program.methods
-        .placeBet(betId, option, new BN(amount))
-        .accounts({
-          user: provider.wallet.publicKey,
-        })
+        .placeBet(betId, option, new BN(amount), null, [])
+        .accountsPartial(placeBetAccounts(bet))
         .rpc();
     },
     onSuccess: async (signature) => {
@@ -162,15 +230,15 @@ export function useBettingProgram() {
     mutationKey: ["betting", "resolve-bet", { cluster }],
     mutationFn: async ({ betId, winningOption, resultDetails }: ResolveBetParams) => {
       if (!betId) throw new Error("Bet ID is required");
-      if (winningOption !== 1 && winningOption !== 2) throw new Error("Winning option must be 1 or 2");
+      if (winningOption !== 1 && winningOption !== 2 && winningOption !== DRAW_OUTCOME)
+        throw new Error("Winning option must be 1, 2 or a draw");
       if (!resultDetails.trim()) throw new Error("Result details are required");
 
-      const [betPda] = PublicKey.findProgramAddressSync([Buffer.from("bet"), Buffer.from(betId)], programId);
-
       return program.methods
-        .resolveBet(betId, winningOption, resultDetails)
-        .accounts({
-          creator: provider.wallet.publicKey,
+        .resolveBet(betId, winningOption, resultDetails, null, [])
+        .accountsPartial({
+          houseVault: null,
+          resolver: provider.wallet.publicKey,
         })
         .rpc();
     },
@@ -272,6 +340,10 @@ export function useBettingProgram() {
     allBets,
     getUserBets,
     getProgramAccount,
+    platformConfig,
+    categories,
+    categoryNames,
+    placeBetAccounts,
     createBet,
     placeBet,
     resolveBet,
@@ -284,7 +356,7 @@ export function useBettingProgram() {
 export function useBettingProgramAccount({ betId }: { betId: string }) {
   const { cluster } = useCluster();
   const transactionToast = useTransactionToast();
-  const { program, allBets, getUserBets, programId } = useBettingProgram();
+  const { program, allBets, getUserBets, programId, categoryNames, placeBetAccounts } = useBettingProgram();
   const provider = useAnchorProvider();
 
   const [betPda] = useMemo(
@@ -344,11 +416,12 @@ export function useBettingProgramAccount({ betId }: { betId: string }) {
       if (option !== 1 && option !== 2) throw new Error("Option must be 1 or 2");
       if (amount <= 0) throw new Error("Amount must be positive");
 
+      const bet = betQuery.data ?? (await betQuery.refetch()).data;
+      if (!bet) throw new Error("Bet not found");
+
       return program.methods
-        .placeBet(betId, option, new BN(amount))
-        .accounts({
-          user: provider.wallet.publicKey,
-        })
+        .placeBet(betId, option, new BN(amount), null, [])
+        .accountsPartial(placeBetAccounts(bet))
         .rpc();
     },
     onSuccess: async (tx) => {
@@ -371,13 +444,15 @@ export function useBettingProgramAccount({ betId }: { betId: string }) {
   const resolveBetMutation = useMutation({
     mutationKey: ["betting", "resolve-bet", { cluster, betId }],
     mutationFn: async ({ winningOption, resultDetails }: { winningOption: number; resultDetails: string }) => {
-      if (winningOption !== 1 && winningOption !== 2) throw new Error("Winning option must be 1 or 2");
+      if (winningOption !== 1 && winningOption !== 2 && winningOption !== DRAW_OUTCOME)
+        throw new Error("Winning option must be 1, 2 or a draw");
       if (!resultDetails.trim()) throw new Error("Result details are required");
 
       return program.methods
-        .resolveBet(betId, winningOption, resultDetails)
-        .accounts({
-          creator: provider.wallet.publicKey,
+        .resolveBet(betId, winningOption, resultDetails, null, [])
+        .accountsPartial({
+          houseVault: null,
+          resolver: provider.wallet.publicKey,
         })
         .rpc();
     },
@@ -463,19 +538,34 @@ export function useBettingProgramAccount({ betId }: { betId: string }) {
   const canUserClaimWinnings = useMemo(() => {
     if (!betQuery.data || !userBetQuery.data) return false;
 
-    return (
-      betQuery.data.isResolved &&
-      userBetQuery.data.option === betQuery.data.winningOption &&
-      !userBetQuery.data.isClaimed
-    );
+    // Draws refund everyone, split resolutions pay every option with a weight and
+    // scalar markets pay both sides
+    const { winningOption, resolutionWeightsBps, marketType } = betQuery.data;
+    const option = userBetQuery.data.option;
+    const isPaid =
+      winningOption === DRAW_OUTCOME ||
+      "scalar" in marketType ||
+      (resolutionWeightsBps.length > 0 ? (resolutionWeightsBps[option - 1] ?? 0) > 0 : option === winningOption);
+
+    return betQuery.data.isResolved && isPaid && !userBetQuery.data.isClaimed;
   }, [betQuery.data, userBetQuery.data]);
 
   const canUserClaimMakerFees = useMemo(() => {
     if (!betQuery.data) return false;
 
-    return betQuery.data.isResolved && betQuery.data.makerFeeCollected.gt(new BN(0)) && isUserBetCreator;
+    return (
+      (betQuery.data.isResolved || betQuery.data.isVoided) &&
+      betQuery.data.makerFeeCollected.gt(new BN(0)) &&
+      isUserBetCreator
+    );
   }, [betQuery.data, isUserBetCreator]);
 
+  const categoryName = useMemo(() => {
+    if (!betQuery.data) return "";
+
+    return categoryNames.get(betQuery.data.category.toBase58()) ?? "";
+  }, [betQuery.data, categoryNames]);
+
   const betTimeRemaining = useMemo(() => {
     if (!betQuery.data) return 0;
 
@@ -500,6 +590,7 @@ export function useBettingProgramAccount({ betId }: { betId: string }) {
     isUserBetCreator,
     canUserClaimWinnings,
     canUserClaimMakerFees,
+    categoryName,
     betTimeRemaining,
   };
 }